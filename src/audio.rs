@@ -0,0 +1,295 @@
+use bevy::audio::{Decodable, PlaybackMode, Source, Volume};
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::settings::Settings;
+
+const SAMPLE_RATE: u32 = 44_100;
+/// Fade-in of every note, so they start without a click.
+const ATTACK_SECS: f32 = 0.005;
+/// Sound entities outlive their sound by this much, then go. Bevy never finishes
+/// them when there is no output device.
+const CLEANUP_MARGIN_SECS: f32 = 0.5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Wave {
+    Sine,
+    Square,
+    Triangle,
+    /// White noise: the frequency is ignored.
+    Noise,
+}
+
+impl Wave {
+    /// Value at `phase`, in turns.
+    fn sample(self, phase: f32, noise: &mut u32) -> f32 {
+        match self {
+            Wave::Sine => (phase * std::f32::consts::TAU).sin(),
+            Wave::Square => if phase < 0.5 { 1.0 } else { -1.0 },
+            Wave::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            Wave::Noise => {
+                // xorshift32
+                *noise ^= *noise << 13;
+                *noise ^= *noise >> 17;
+                *noise ^= *noise << 5;
+                *noise as f32 / u32::MAX as f32 * 2.0 - 1.0
+            }
+        }
+    }
+}
+
+/// Frequency in Hz, 0 for a rest, and length in seconds.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Note {
+    pub freq: f32,
+    pub secs: f32,
+}
+
+const fn note(freq: f32, secs: f32) -> Note {
+    Note { freq, secs }
+}
+
+/// Sound generated from a few notes instead of read from a file, so the game
+/// ships without audio assets.
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct Synth {
+    pub wave: Wave,
+    pub notes: Arc<[Note]>,
+    pub gain: f32,
+}
+
+impl Synth {
+    pub fn new(wave: Wave, gain: f32, notes: &[Note]) -> Self {
+        Self { wave, notes: notes.into(), gain }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.notes.iter().map(|n| n.secs).sum()
+    }
+}
+
+pub struct SynthDecoder {
+    synth: Synth,
+    note: usize,
+    sample: u32,
+    phase: f32,
+    noise: u32,
+}
+
+impl Iterator for SynthDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        loop {
+            let note = *self.synth.notes.get(self.note)?;
+            let length = (note.secs * SAMPLE_RATE as f32) as u32;
+            if self.sample >= length {
+                self.note += 1;
+                self.sample = 0;
+                continue;
+            }
+
+            let t = self.sample as f32 / SAMPLE_RATE as f32;
+            let attack = (t / ATTACK_SECS).min(1.0);
+            let decay = (1.0 - self.sample as f32 / length as f32).powi(2);
+            let value = if note.freq > 0.0 || self.synth.wave == Wave::Noise {
+                self.synth.wave.sample(self.phase, &mut self.noise)
+            } else {
+                0.0
+            };
+
+            self.phase = (self.phase + note.freq / SAMPLE_RATE as f32).fract();
+            self.sample += 1;
+            return Some(value * attack * decay * self.synth.gain);
+        }
+    }
+}
+
+impl Source for SynthDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(self.synth.duration()))
+    }
+}
+
+impl Decodable for Synth {
+    type DecoderItem = f32;
+    type Decoder = SynthDecoder;
+
+    fn decoder(&self) -> SynthDecoder {
+        SynthDecoder { synth: self.clone(), note: 0, sample: 0, phase: 0.0, noise: 0x9E37_79B9 }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Sfx {
+    Click,
+    ComboUp,
+    Purchase,
+    GoldenSpawn,
+    GoldenClick,
+    Achievement,
+}
+
+impl Sfx {
+    pub const ALL: [Sfx; 6] = [
+        Sfx::Click,
+        Sfx::ComboUp,
+        Sfx::Purchase,
+        Sfx::GoldenSpawn,
+        Sfx::GoldenClick,
+        Sfx::Achievement,
+    ];
+
+    fn synth(self) -> Synth {
+        match self {
+            Sfx::Click => Synth::new(Wave::Triangle, 0.35, &[note(880.0, 0.05)]),
+            Sfx::ComboUp => Synth::new(Wave::Square, 0.12, &[note(523.3, 0.06), note(659.3, 0.06), note(784.0, 0.1)]),
+            Sfx::Purchase => Synth::new(Wave::Sine, 0.4, &[note(659.3, 0.06), note(987.8, 0.12)]),
+            Sfx::GoldenSpawn => Synth::new(Wave::Sine, 0.3, &[note(1318.5, 0.08), note(1568.0, 0.08), note(2093.0, 0.2)]),
+            Sfx::GoldenClick => Synth::new(Wave::Square, 0.12, &[
+                note(784.0, 0.05), note(1046.5, 0.05), note(1318.5, 0.05), note(1568.0, 0.15),
+            ]),
+            Sfx::Achievement => Synth::new(Wave::Triangle, 0.4, &[
+                note(523.3, 0.1), note(659.3, 0.1), note(784.0, 0.1), note(1046.5, 0.3),
+            ]),
+        }
+    }
+}
+
+/// Background loop, a slow pentatonic tune.
+fn music() -> Synth {
+    const BEAT: f32 = 0.3;
+    let tune = [
+        261.6, 329.6, 392.0, 329.6, 440.0, 392.0, 329.6, 0.0,
+        293.7, 392.0, 440.0, 392.0, 329.6, 293.7, 261.6, 0.0,
+    ];
+    let notes: Vec<Note> = tune.iter().map(|&freq| note(freq, BEAT)).collect();
+    Synth::new(Wave::Triangle, 0.15, &notes)
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlaySound(pub Sfx);
+
+#[derive(Resource)]
+pub struct SoundBank(HashMap<Sfx, Handle<Synth>>);
+
+/// A playing sound effect, despawned once `0` runs out.
+#[derive(Component)]
+pub struct SoundEffect(Timer);
+
+#[derive(Component)]
+pub struct MusicTrack;
+
+pub fn setup_audio(mut commands: Commands, mut synths: ResMut<Assets<Synth>>, settings: Res<Settings>) {
+    let bank = Sfx::ALL.iter().map(|&sfx| (sfx, synths.add(sfx.synth()))).collect();
+    commands.insert_resource(SoundBank(bank));
+
+    commands.spawn((
+        AudioSourceBundle {
+            source: synths.add(music()),
+            settings: PlaybackSettings {
+                mode: PlaybackMode::Loop,
+                volume: Volume::new(settings.music_gain()),
+                ..default()
+            },
+        },
+        MusicTrack,
+    ));
+}
+
+pub fn play_sound_system(
+    mut commands: Commands,
+    mut events: EventReader<PlaySound>,
+    bank: Res<SoundBank>,
+    synths: Res<Assets<Synth>>,
+    settings: Res<Settings>,
+) {
+    let gain = settings.sfx_gain();
+    for PlaySound(sfx) in events.read() {
+        let Some(handle) = bank.0.get(sfx) else {
+            continue;
+        };
+        if gain <= 0.0 {
+            continue;
+        }
+
+        let duration = synths.get(handle).map_or(0.0, Synth::duration);
+        commands.spawn((
+            AudioSourceBundle {
+                source: handle.clone(),
+                settings: PlaybackSettings::ONCE.with_volume(Volume::new(gain)),
+            },
+            SoundEffect(Timer::from_seconds(duration + CLEANUP_MARGIN_SECS, TimerMode::Once)),
+        ));
+    }
+}
+
+pub fn sound_cleanup_system(
+    mut commands: Commands,
+    mut sounds: Query<(Entity, &mut SoundEffect)>,
+    time: Res<Time>,
+) {
+    for (entity, mut sound) in &mut sounds {
+        sound.0.tick(time.delta());
+        if sound.0.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Follows the music volume and mute while the loop plays. No sink exists without
+/// an output device, so there is simply nothing to update then.
+pub fn music_volume_system(settings: Res<Settings>, music: Query<&AudioSink, With<MusicTrack>>) {
+    if !settings.is_changed() {
+        return;
+    }
+    for sink in &music {
+        sink.set_volume(settings.music_gain());
+    }
+}
+
+pub fn mute_hotkey_system(keys: Res<ButtonInput<KeyCode>>, mut settings: ResMut<Settings>) {
+    if keys.just_pressed(KeyCode::KeyM) {
+        settings.muted = !settings.muted;
+        println!("{}", if settings.muted { "🔇 Son coupé" } else { "🔊 Son rétabli" });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synth_renders_every_note_within_range() {
+        for synth in Sfx::ALL.iter().map(|s| s.synth()).chain([music()]) {
+            let samples: Vec<f32> = synth.decoder().collect();
+            let expected: u32 = synth.notes.iter().map(|n| (n.secs * SAMPLE_RATE as f32) as u32).sum();
+            assert_eq!(samples.len(), expected as usize);
+            assert!(samples.iter().all(|s| s.abs() <= synth.gain));
+            assert!(samples.iter().any(|s| s.abs() > synth.gain * 0.1));
+        }
+    }
+
+    #[test]
+    fn notes_start_and_end_silent() {
+        let samples: Vec<f32> = Synth::new(Wave::Square, 1.0, &[note(440.0, 0.1)]).decoder().collect();
+        assert!(samples[0].abs() < 0.01);
+        assert!(samples[samples.len() - 1].abs() < 0.01);
+
+        let rest: Vec<f32> = Synth::new(Wave::Sine, 1.0, &[note(0.0, 0.1)]).decoder().collect();
+        assert!(rest.iter().all(|s| *s == 0.0));
+    }
+}
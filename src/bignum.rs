@@ -0,0 +1,509 @@
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Beyond this many orders of magnitude apart, the smaller operand of an
+/// addition no longer changes the f64 mantissa.
+const MAX_EXPONENT_GAP: i64 = 17;
+
+/// Operands below this magnitude are combined as plain f64, which keeps whole
+/// numbers whole (`41 + 1` is `42`, not `41.999…`).
+const F64_EXPONENT_LIMIT: i64 = 150;
+
+/// Map key serde_json uses for numbers when `arbitrary_precision` is enabled.
+const SERDE_JSON_NUMBER_TOKEN: &str = "$serde_json::private::Number";
+
+/// Significant digits kept in the mantissa, a few below f64's 17 so the
+/// rounding noise of `mantissa * 10^exponent` is dropped.
+const SIGNIFICANT_DIGITS: i32 = 15;
+
+/// Non-overflowing number for everything cookie-sized: `mantissa * 10^exponent`,
+/// with `1 <= |mantissa| < 10` (or exactly zero).
+///
+/// Serialized as a string (`"1.5e30"`) so precision never depends on the JSON parser.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct BigNum {
+    mantissa: f64,
+    exponent: i64,
+}
+
+impl BigNum {
+    pub const ZERO: BigNum = BigNum { mantissa: 0.0, exponent: 0 };
+    pub const ONE: BigNum = BigNum { mantissa: 1.0, exponent: 0 };
+
+    pub fn new(mantissa: f64, exponent: i64) -> Self {
+        if mantissa == 0.0 || !mantissa.is_finite() {
+            return Self::ZERO;
+        }
+
+        // Scaled straight to a whole number of `SIGNIFICANT_DIGITS` digits: the
+        // power of ten is exact for everyday magnitudes, so this rounds once and
+        // `114.0` stays `1.14e2` instead of `1.1399999999999999e2`.
+        let scale = |shift: i32| match shift {
+            0.. => mantissa / 10f64.powi(shift),
+            -300.. => mantissa * 10f64.powi(-shift),
+            // 10^-shift alone would overflow for subnormals.
+            _ => mantissa * 1e300 * 10f64.powi(-shift - 300),
+        };
+        let low = 10f64.powi(SIGNIFICANT_DIGITS - 1);
+        let mut shift = mantissa.abs().log10().floor() as i32 - (SIGNIFICANT_DIGITS - 1);
+        let mut digits = scale(shift).round();
+        // log10 can be off by one right at powers of ten, and rounding can carry.
+        if digits.abs() >= low * 10.0 {
+            shift += 1;
+            digits = scale(shift).round();
+        } else if digits.abs() < low {
+            shift -= 1;
+            digits = scale(shift).round();
+        }
+        if digits.abs() >= low * 10.0 {
+            digits /= 10.0;
+            shift += 1;
+        }
+
+        Self {
+            mantissa: digits / low,
+            exponent: exponent + (shift + SIGNIFICANT_DIGITS - 1) as i64,
+        }
+    }
+
+    pub fn mantissa(&self) -> f64 {
+        self.mantissa
+    }
+
+    pub fn exponent(&self) -> i64 {
+        self.exponent
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0.0
+    }
+
+    /// Closest f64, `f64::INFINITY` past 1e308.
+    pub fn to_f64(self) -> f64 {
+        if self.exponent > 308 {
+            return f64::INFINITY.copysign(self.mantissa);
+        }
+        if self.exponent < -324 {
+            return 0.0;
+        }
+
+        // The mantissa as a whole number first, so `1.14e2` gives `114.0` and
+        // not `113.99999999999999`.
+        let digits = (self.mantissa * 10f64.powi(SIGNIFICANT_DIGITS - 1)).round();
+        let shift = self.exponent as i32 - (SIGNIFICANT_DIGITS - 1);
+        if shift >= 0 {
+            digits * 10f64.powi(shift)
+        } else {
+            digits / 10f64.powi(-shift)
+        }
+    }
+
+    pub fn floor(self) -> Self {
+        // Past 2^53 every f64 is already an integer.
+        if self.exponent >= 16 {
+            return self;
+        }
+        Self::from(self.to_f64().floor())
+    }
+
+    /// `self^power`, exact for small results and through logarithms otherwise.
+    pub fn powf(self, power: f64) -> Self {
+        if self.is_zero() {
+            return Self::ZERO;
+        }
+
+        let direct = self.to_f64().powf(power);
+        if direct.is_finite() && direct != 0.0 {
+            return Self::from(direct);
+        }
+
+        let log = self.log10() * power;
+        let exponent = log.floor();
+        Self::new(10f64.powf(log - exponent), exponent as i64)
+    }
+
+    pub fn log10(&self) -> f64 {
+        self.mantissa.log10() + self.exponent as f64
+    }
+
+    /// `self / other`, None when `other` is zero.
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        if other.is_zero() {
+            return None;
+        }
+        if self.fits_f64() && other.fits_f64() {
+            return Some(Self::from(self.to_f64() / other.to_f64()));
+        }
+        Some(Self::new(self.mantissa / other.mantissa, self.exponent - other.exponent))
+    }
+
+    fn fits_f64(&self) -> bool {
+        self.exponent.abs() < F64_EXPONENT_LIMIT
+    }
+}
+
+impl From<f64> for BigNum {
+    fn from(value: f64) -> Self {
+        Self::new(value, 0)
+    }
+}
+
+impl From<u128> for BigNum {
+    fn from(value: u128) -> Self {
+        Self::new(value as f64, 0)
+    }
+}
+
+impl From<u64> for BigNum {
+    fn from(value: u64) -> Self {
+        Self::new(value as f64, 0)
+    }
+}
+
+impl PartialOrd for BigNum {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let sign = |n: &BigNum| if n.mantissa > 0.0 { 1 } else if n.mantissa < 0.0 { -1 } else { 0 };
+        let (a, b) = (sign(self), sign(other));
+        if a != b || a == 0 {
+            return a.partial_cmp(&b);
+        }
+
+        let magnitude = self.exponent.cmp(&other.exponent)
+            .then(self.mantissa.abs().partial_cmp(&other.mantissa.abs())?);
+        Some(if a > 0 { magnitude } else { magnitude.reverse() })
+    }
+}
+
+impl Add for BigNum {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        if self.is_zero() {
+            return other;
+        }
+        if other.is_zero() {
+            return self;
+        }
+
+        if self.fits_f64() && other.fits_f64() {
+            return Self::from(self.to_f64() + other.to_f64());
+        }
+
+        let (big, small) = if self.exponent >= other.exponent { (self, other) } else { (other, self) };
+        let gap = big.exponent - small.exponent;
+        if gap > MAX_EXPONENT_GAP {
+            return big;
+        }
+        Self::new(big.mantissa + small.mantissa / 10f64.powi(gap as i32), big.exponent)
+    }
+}
+
+impl Sub for BigNum {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + Self { mantissa: -other.mantissa, exponent: other.exponent }
+    }
+}
+
+impl Mul for BigNum {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        if self.fits_f64() && other.fits_f64() {
+            return Self::from(self.to_f64() * other.to_f64());
+        }
+        Self::new(self.mantissa * other.mantissa, self.exponent + other.exponent)
+    }
+}
+
+impl Mul<f64> for BigNum {
+    type Output = Self;
+
+    fn mul(self, other: f64) -> Self {
+        self * Self::from(other)
+    }
+}
+
+impl Div for BigNum {
+    type Output = Self;
+
+    /// Dividing by zero is a bug in the caller: use `checked_div` when it can happen.
+    fn div(self, other: Self) -> Self {
+        debug_assert!(!other.is_zero(), "division de {:?} par zéro", self);
+        self.checked_div(other).unwrap_or(Self::ZERO)
+    }
+}
+
+impl Div<f64> for BigNum {
+    type Output = Self;
+
+    fn div(self, other: f64) -> Self {
+        self / Self::from(other)
+    }
+}
+
+impl AddAssign for BigNum {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for BigNum {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<f64> for BigNum {
+    fn mul_assign(&mut self, other: f64) {
+        *self = *self * other;
+    }
+}
+
+impl Sum for BigNum {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, n| acc + n)
+    }
+}
+
+impl fmt::Debug for BigNum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}e{}", self.mantissa, self.exponent)
+    }
+}
+
+impl fmt::Display for BigNum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}e{}", self.mantissa, self.exponent)
+    }
+}
+
+impl FromStr for BigNum {
+    type Err = String;
+
+    /// Accepts `"1.5e30"` as well as plain numbers like `"123456789012345678901234"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || format!("nombre invalide : {}", s);
+
+        match s.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => {
+                let mantissa: f64 = mantissa.parse().map_err(|_| invalid())?;
+                let exponent: i64 = exponent.parse().map_err(|_| invalid())?;
+                Ok(Self::new(mantissa, exponent))
+            }
+            None => {
+                let value: f64 = s.parse().map_err(|_| invalid())?;
+                if !value.is_finite() {
+                    return Err(invalid());
+                }
+                Ok(Self::from(value))
+            }
+        }
+    }
+}
+
+impl Serialize for BigNum {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// Saves always hold strings, hand-written data files may also use plain numbers.
+impl<'de> Deserialize<'de> for BigNum {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BigNumVisitor;
+
+        impl<'de> Visitor<'de> for BigNumVisitor {
+            type Value = BigNum;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a number or a string like \"1.5e30\"")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<BigNum, E> {
+                value.parse().map_err(E::custom)
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<BigNum, E> {
+                Ok(BigNum::from(value))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<BigNum, E> {
+                Ok(BigNum::from(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<BigNum, E> {
+                Ok(BigNum::from(value as f64))
+            }
+
+            /// serde_json's `arbitrary_precision` hands numbers over as a
+            /// one-entry map holding their source text.
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<BigNum, A::Error> {
+                match map.next_key::<String>()? {
+                    Some(key) if key == SERDE_JSON_NUMBER_TOKEN => {
+                        map.next_value::<String>()?.parse().map_err(de::Error::custom)
+                    }
+                    _ => Err(de::Error::invalid_type(de::Unexpected::Map, &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(BigNumVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::{QuickCheck, TestResult};
+
+    fn close(a: BigNum, b: BigNum) -> bool {
+        a.exponent == b.exponent && (a.mantissa - b.mantissa).abs() < 1e-12
+    }
+
+    #[test]
+    fn normalizes_mantissa() {
+        let n = BigNum::from(12_345.0);
+        assert_eq!(n.exponent(), 4);
+        assert!((n.mantissa() - 1.2345).abs() < 1e-12);
+
+        let n = BigNum::new(0.05, 10);
+        assert_eq!(n.exponent(), 8);
+        assert!((n.mantissa() - 5.0).abs() < 1e-12);
+
+        assert_eq!(BigNum::new(0.0, 50), BigNum::ZERO);
+        assert_eq!(BigNum::from(1_000.0).exponent(), 3);
+        assert_eq!(BigNum::from(1_000.0).mantissa(), 1.0);
+    }
+
+    #[test]
+    fn arithmetic_matches_f64_in_range() {
+        let a = BigNum::from(1_500.0);
+        let b = BigNum::from(250.0);
+        assert_eq!((a + b).to_f64(), 1_750.0);
+        assert_eq!((a - b).to_f64(), 1_250.0);
+        assert_eq!((a * b).to_f64(), 375_000.0);
+        assert_eq!((a / b).to_f64(), 6.0);
+        assert_eq!((b - a).to_f64(), -1_250.0);
+        assert!((a - a).is_zero());
+        assert_eq!(a.checked_div(b), Some(BigNum::from(6.0)));
+        assert_eq!(a.checked_div(BigNum::ZERO), None);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "par zéro")]
+    fn dividing_by_zero_is_a_bug() {
+        let _ = BigNum::ONE / BigNum::ZERO;
+    }
+
+    #[test]
+    fn whole_numbers_stay_whole() {
+        assert_eq!(BigNum::from(41.0) + BigNum::ONE, BigNum::from(42.0));
+        assert_eq!(BigNum::from(1.14) * BigNum::from(100.0), BigNum::from(114.0));
+        assert_eq!((BigNum::from(1.14) * BigNum::from(100.0)).floor(), BigNum::from(114.0));
+        assert_eq!(BigNum::from(342.0) / BigNum::from(3.0), BigNum::from(114.0));
+
+        let mut total = BigNum::ZERO;
+        for _ in 0..1_000 {
+            total += BigNum::from(0.1);
+        }
+        assert_eq!(total, BigNum::from(100.0));
+    }
+
+    #[test]
+    fn f64_round_trip_is_stable() {
+        fn property(value: f64) -> TestResult {
+            if !value.is_finite() || value.abs() > 1e300 || value.abs() < 1e-300 {
+                return TestResult::discard();
+            }
+            let n = BigNum::from(value);
+            TestResult::from_bool(
+                BigNum::from(n.to_f64()) == n
+                    && n.to_string().parse::<BigNum>() == Ok(n)
+                    && (n.to_f64() - value).abs() <= value.abs() * 1e-14,
+            )
+        }
+        QuickCheck::new().tests(2_000).quickcheck(property as fn(f64) -> TestResult);
+    }
+
+    #[test]
+    fn goes_far_beyond_u128_and_f64() {
+        let huge = BigNum::new(3.0, 24).powf(20.0);
+        assert_eq!(huge.exponent(), 489);
+        assert_eq!(huge.to_f64(), f64::INFINITY);
+
+        let sum = huge + BigNum::ONE;
+        assert_eq!(sum, huge);
+        assert!(close(huge * BigNum::from(2.0) / BigNum::from(2.0), huge));
+        assert!(huge > BigNum::from(u128::MAX));
+    }
+
+    #[test]
+    fn ordering_handles_sign_and_magnitude() {
+        let values = [
+            BigNum::from(-5e10),
+            BigNum::from(-2.0),
+            BigNum::ZERO,
+            BigNum::from(0.5),
+            BigNum::from(9.0),
+            BigNum::from(10.0),
+            BigNum::new(1.0, 400),
+        ];
+        for pair in values.windows(2) {
+            assert!(pair[0] < pair[1], "{:?} < {:?}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn floor_and_pow() {
+        assert_eq!(BigNum::from(114.999).floor().to_f64(), 114.0);
+        assert_eq!(BigNum::from(100.0).powf(0.5).to_f64(), 10.0);
+        assert!(close(BigNum::from(10.0).powf(500.0), BigNum::new(1.0, 500)));
+        assert_eq!(BigNum::ZERO.powf(3.0), BigNum::ZERO);
+    }
+
+    #[test]
+    fn string_round_trip() {
+        for n in [
+            BigNum::ZERO,
+            BigNum::from(0.1),
+            BigNum::from(123_456_789_012_345_678_901_234_u128),
+            BigNum::new(7.77, 1_234),
+        ] {
+            assert_eq!(n.to_string().parse::<BigNum>().unwrap(), n);
+            let json = serde_json::to_string(&n).unwrap();
+            assert_eq!(serde_json::from_str::<BigNum>(&json).unwrap(), n);
+        }
+
+        assert_eq!(
+            "123456789012345678901234".parse::<BigNum>().unwrap(),
+            BigNum::from(123_456_789_012_345_678_901_234_u128),
+        );
+        assert!("cookies".parse::<BigNum>().is_err());
+    }
+
+    #[test]
+    fn data_files_may_use_plain_numbers() {
+        assert_eq!(ron::from_str::<BigNum>("1500").unwrap(), BigNum::from(1_500.0));
+        assert_eq!(ron::from_str::<BigNum>("0.1").unwrap(), BigNum::from(0.1));
+        assert_eq!(ron::from_str::<BigNum>("3e24").unwrap(), BigNum::new(3.0, 24));
+        assert_eq!(ron::from_str::<BigNum>("\"3e24\"").unwrap(), BigNum::new(3.0, 24));
+        assert!(ron::from_str::<BigNum>("[1]").is_err());
+
+        assert_eq!(serde_json::from_str::<BigNum>("1.5").unwrap(), BigNum::from(1.5));
+        assert_eq!(serde_json::from_str::<BigNum>("1500").unwrap(), BigNum::from(1_500.0));
+        assert_eq!(
+            serde_json::from_str::<BigNum>("123456789012345678901234").unwrap(),
+            BigNum::from(123_456_789_012_345_678_901_234_u128),
+        );
+        assert!(serde_json::from_str::<BigNum>("{\"c\": 1.5}").is_err());
+    }
+}
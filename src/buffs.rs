@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+use crate::locale::{tr, tr_with};
+use crate::resources::GameState;
+
+/// What a timed buff multiplies.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum BuffTarget {
+    Production,
+    Click,
+    /// Output of one building, by id.
+    Building(String),
+    /// How fast the golden cookie timer runs.
+    GoldenSpawnRate,
+}
+
+/// What granting a buff that is already running does.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stacking {
+    /// Starts the timer over.
+    Refresh,
+    /// Adds the new duration to what is left.
+    Extend,
+    /// Runs side by side with the others, multipliers multiplied.
+    Stack,
+}
+
+/// Everything about a buff but its target, which is chosen when it is granted.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BuffDef {
+    pub id: &'static str,
+    pub emoji: &'static str,
+    /// Below 1 for a debuff.
+    pub multiplier: f64,
+    pub duration: f64,
+    pub stacking: Stacking,
+}
+
+pub const FRENZY: BuffDef = BuffDef {
+    id: "frenzy", emoji: "🔥", multiplier: 7.0, duration: 77.0, stacking: Stacking::Refresh,
+};
+pub const CLICK_FRENZY: BuffDef = BuffDef {
+    id: "click_frenzy", emoji: "👆", multiplier: 777.0, duration: 13.0, stacking: Stacking::Refresh,
+};
+pub const BUILDING_BOOST: BuffDef = BuffDef {
+    id: "building_boost", emoji: "🏗️", multiplier: 10.0, duration: 30.0, stacking: Stacking::Stack,
+};
+pub const GOLDEN_RAIN: BuffDef = BuffDef {
+    id: "golden_rain", emoji: "🌟", multiplier: 4.0, duration: 60.0, stacking: Stacking::Extend,
+};
+pub const CLOT: BuffDef = BuffDef {
+    id: "clot", emoji: "🩸", multiplier: 0.5, duration: 66.0, stacking: Stacking::Extend,
+};
+
+/// Every buff the game grants, looked up by id when a save is loaded.
+pub const BUFF_DEFS: [BuffDef; 5] = [FRENZY, CLICK_FRENZY, BUILDING_BOOST, GOLDEN_RAIN, CLOT];
+
+pub fn buff_def(id: &str) -> Option<&'static BuffDef> {
+    BUFF_DEFS.iter().find(|d| d.id == id)
+}
+
+impl BuffDef {
+    pub fn grant(&self, target: BuffTarget) -> Buff {
+        Buff { def: *self, target, duration: self.duration, remaining: self.duration }
+    }
+}
+
+/// A running buff.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Buff {
+    pub def: BuffDef,
+    pub target: BuffTarget,
+    /// Seconds granted in total, extensions included.
+    pub duration: f64,
+    pub remaining: f64,
+}
+
+impl Buff {
+    pub fn is_debuff(&self) -> bool {
+        self.def.multiplier < 1.0
+    }
+
+    /// Name in the current language. Building buffs name their building.
+    pub fn name(&self, state: &GameState) -> String {
+        let key = format!("buff.{}.name", self.def.id);
+        match &self.target {
+            BuffTarget::Building(id) => {
+                let building = state.building(id).map_or_else(|| id.clone(), |u| u.name.clone());
+                tr_with(&key, &[("building", building)])
+            }
+            _ => tr(&key),
+        }
+    }
+
+    /// Share of the duration still to run, from 1 down to 0.
+    pub fn fraction_left(&self) -> f32 {
+        if self.duration <= 0.0 {
+            0.0
+        } else {
+            (self.remaining / self.duration).clamp(0.0, 1.0) as f32
+        }
+    }
+}
+
+/// Buffs running right now, in the order they were granted. Read like a slice,
+/// changed only through `add`, `tick` and `clear`.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ActiveBuffs(Vec<Buff>);
+
+impl std::ops::Deref for ActiveBuffs {
+    type Target = [Buff];
+
+    fn deref(&self) -> &[Buff] {
+        &self.0
+    }
+}
+
+impl ActiveBuffs {
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Adds `buff` following its stacking policy against the same buff on the same target.
+    pub fn add(&mut self, buff: Buff) {
+        let existing = self.0.iter_mut()
+            .find(|b| b.def.id == buff.def.id && b.target == buff.target);
+        match (buff.def.stacking, existing) {
+            (Stacking::Refresh, Some(existing)) => {
+                existing.remaining = existing.remaining.max(buff.remaining);
+                existing.duration = existing.remaining;
+            }
+            (Stacking::Extend, Some(existing)) => {
+                existing.remaining += buff.remaining;
+                existing.duration = existing.remaining;
+            }
+            _ => self.0.push(buff),
+        }
+    }
+
+    /// Counts every buff down by `dt` seconds. Returns whether any ran out.
+    pub fn tick(&mut self, dt: f64) -> bool {
+        for buff in &mut self.0 {
+            buff.remaining -= dt;
+        }
+        let before = self.0.len();
+        self.0.retain(|b| b.remaining > 0.0);
+        self.0.len() != before
+    }
+
+    /// Product of the multipliers of every buff aimed at `target`.
+    pub fn multiplier(&self, target: &BuffTarget) -> f64 {
+        self.0.iter()
+            .filter(|b| &b.target == target)
+            .map(|b| b.def.multiplier)
+            .product()
+    }
+}
+
+impl GameState {
+    pub fn add_buff(&mut self, buff: Buff) {
+        self.buffs.add(buff);
+        self.refresh_production();
+    }
+
+    /// Counts the buffs down and drops the finished ones.
+    pub fn tick_buffs(&mut self, dt: f64) {
+        if self.buffs.tick(dt) {
+            self.refresh_production();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffs_multiply_production_until_they_run_out() {
+        let mut state = GameState::default();
+        state.upgrades[0].count = 10;
+        state.powerups[0].count = 1;
+        state.refresh_production();
+        let (cps, click) = (state.cookies_per_second, state.cookies_per_click);
+
+        state.add_buff(FRENZY.grant(BuffTarget::Production));
+        state.add_buff(CLICK_FRENZY.grant(BuffTarget::Click));
+        assert_eq!(state.cookies_per_second, cps * 7.0);
+        assert_eq!(state.cookies_per_click, click * 777.0);
+
+        state.tick_buffs(13.0);
+        assert_eq!(state.buffs.len(), 1);
+        assert_eq!(state.cookies_per_click, click);
+        assert_eq!(state.cookies_per_second, cps * 7.0);
+
+        state.tick_buffs(64.0);
+        assert!(state.buffs.is_empty());
+        assert_eq!(state.cookies_per_second, cps);
+    }
+
+    #[test]
+    fn building_buffs_only_touch_their_building() {
+        let mut state = GameState::default();
+        state.upgrades[0].count = 10;
+        state.upgrades[1].count = 1;
+        state.refresh_production();
+        let before = state.cookies_per_second;
+        let cursor = state.building_cps(0) * 10.0;
+
+        let id = state.upgrades[0].id.clone();
+        state.add_buff(BUILDING_BOOST.grant(BuffTarget::Building(id)));
+        assert_eq!(state.cookies_per_second, before + cursor * 9.0);
+        assert_eq!(state.building_cps(0) * 10.0, cursor * 10.0);
+    }
+
+    #[test]
+    fn stacking_policies() {
+        let mut buffs = ActiveBuffs::default();
+
+        buffs.add(FRENZY.grant(BuffTarget::Production));
+        buffs.tick(70.0);
+        buffs.add(FRENZY.grant(BuffTarget::Production));
+        assert_eq!(buffs.len(), 1);
+        assert_eq!(buffs[0].remaining, 77.0);
+
+        buffs.add(CLOT.grant(BuffTarget::Production));
+        buffs.add(CLOT.grant(BuffTarget::Production));
+        assert_eq!(buffs.len(), 2);
+        assert_eq!(buffs.get(1).unwrap().remaining, 132.0);
+        assert_eq!(buffs.multiplier(&BuffTarget::Production), 3.5);
+
+        let mine = BuffTarget::Building("mine".into());
+        buffs.add(BUILDING_BOOST.grant(mine.clone()));
+        buffs.add(BUILDING_BOOST.grant(mine.clone()));
+        assert_eq!(buffs.len(), 4);
+        assert_eq!(buffs.multiplier(&mine), 100.0);
+        assert_eq!(buffs.multiplier(&BuffTarget::Click), 1.0);
+
+        assert!(buffs.tick(100.0));
+        assert_eq!(buffs.len(), 1);
+        assert!(buffs[0].is_debuff());
+    }
+
+    #[test]
+    fn every_def_is_found_by_id() {
+        for def in BUFF_DEFS {
+            assert_eq!(buff_def(def.id), Some(&def));
+        }
+        assert_eq!(buff_def("inconnu"), None);
+    }
+}
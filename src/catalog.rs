@@ -0,0 +1,603 @@
+use bevy::asset::io::file::FileAssetReader;
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, LazyLock, PoisonError, RwLock};
+use crate::achievements::{Achievement, AchievementRequirement};
+use crate::bignum::BigNum;
+use crate::challenges::challenge_def;
+use crate::heavenly::{HeavenlyEffect, HeavenlyUpgrade};
+use crate::locale::{tr_count, tr_or, tr_with};
+use crate::powerups::{PowerUp, PowerUpEffect};
+use crate::upgrades::Upgrade;
+use crate::utils::{format_decimal, format_number};
+
+/// Base catalogs under `assets/`, merged in this order.
+pub const CATALOG_FILES: [&str; 5] = [
+    "data/buildings.ron",
+    "data/powerups.ron",
+    "data/achievements.ron",
+    "data/heavenly.ron",
+    "data/rules.ron",
+];
+/// Every `.ron` file in here is a content pack, merged after the base catalogs by file name.
+pub const PACKS_DIR: &str = "data/packs";
+
+/// Copies of the base catalogs, used when the files are missing or broken.
+const EMBEDDED_FILES: [&str; 5] = [
+    include_str!("../assets/data/buildings.ron"),
+    include_str!("../assets/data/powerups.ron"),
+    include_str!("../assets/data/achievements.ron"),
+    include_str!("../assets/data/heavenly.ron"),
+    include_str!("../assets/data/rules.ron"),
+];
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct BuildingDef {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub emoji: String,
+    pub base_cost: BigNum,
+    pub cps: BigNum,
+    #[serde(default)]
+    pub tier: u128,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PowerUpDef {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub emoji: String,
+    pub base_cost: BigNum,
+    /// Cookies per click per unit, for `Flat` power-ups.
+    #[serde(default)]
+    pub multiplier: BigNum,
+    #[serde(default)]
+    pub effect: PowerUpEffect,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AchievementDef {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub emoji: String,
+    pub requirement: AchievementRequirement,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct HeavenlyDef {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub emoji: String,
+    pub cost: BigNum,
+    /// Ids of the heavenly upgrades that must all be bought first.
+    #[serde(default)]
+    pub parents: Vec<String>,
+    pub effect: HeavenlyEffect,
+}
+
+/// Economy numbers designers tune without touching the content lists.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    /// Share of the production credited while the game is closed, before heavenly upgrades.
+    pub offline_efficiency: f64,
+    /// Offline time beyond this is ignored.
+    pub offline_max_hours: f64,
+    /// Fraction of a building's price given back when it is sold.
+    pub sell_refund: f64,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            offline_efficiency: 0.5,
+            offline_max_hours: 24.0,
+            sell_refund: 0.5,
+        }
+    }
+}
+
+impl Rules {
+    fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.offline_efficiency) {
+            return Err("offline_efficiency doit être entre 0 et 1".into());
+        }
+        if self.offline_max_hours.is_nan() || self.offline_max_hours < 0.0 {
+            return Err("offline_max_hours doit être positif".into());
+        }
+        if !(0.0..=1.0).contains(&self.sell_refund) {
+            return Err("sell_refund doit être entre 0 et 1".into());
+        }
+        Ok(())
+    }
+
+    pub fn offline_max_seconds(&self) -> u64 {
+        (self.offline_max_hours * 3_600.0) as u64
+    }
+}
+
+/// One data file. Every section is optional so a content pack can add just a
+/// couple of buildings.
+#[derive(Asset, TypePath, Deserialize, Clone, Default, PartialEq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContentPack {
+    pub buildings: Vec<BuildingDef>,
+    pub powerups: Vec<PowerUpDef>,
+    pub achievements: Vec<AchievementDef>,
+    pub heavenly: Vec<HeavenlyDef>,
+    /// Replaces the rules of the files merged before it.
+    pub rules: Option<Rules>,
+}
+
+impl ContentPack {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        // Lets files write `rules: (...)` rather than `rules: Some((...))`.
+        let options = ron::Options::default().with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME);
+        let pack: ContentPack = options.from_str(text).map_err(|e| e.to_string())?;
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    /// Checks that only need this file.
+    fn validate(&self) -> Result<(), String> {
+        let ids = self.buildings.iter().map(|b| &b.id)
+            .chain(self.powerups.iter().map(|p| &p.id))
+            .chain(self.achievements.iter().map(|a| &a.id))
+            .chain(self.heavenly.iter().map(|h| &h.id));
+        for id in ids {
+            if !is_valid_id(id) {
+                return Err(format!("id invalide : {:?} (a-z, 0-9 et _ uniquement)", id));
+            }
+        }
+
+        for building in &self.buildings {
+            if building.name.trim().is_empty() {
+                return Err("bâtiment sans nom".into());
+            }
+            if building.base_cost <= BigNum::ZERO || building.cps < BigNum::ZERO {
+                return Err(format!("{} : coût ou production invalide", building.name));
+            }
+        }
+        for powerup in &self.powerups {
+            if powerup.name.trim().is_empty() {
+                return Err("power-up sans nom".into());
+            }
+            if powerup.base_cost <= BigNum::ZERO || powerup.multiplier < BigNum::ZERO {
+                return Err(format!("{} : coût ou multiplicateur invalide", powerup.name));
+            }
+            if !powerup.effect.is_valid() {
+                return Err(format!("{} : effet invalide", powerup.name));
+            }
+        }
+        if self.achievements.iter().any(|a| a.name.trim().is_empty()) {
+            return Err("succès sans nom".into());
+        }
+        for upgrade in &self.heavenly {
+            if upgrade.name.trim().is_empty() {
+                return Err("amélioration céleste sans nom".into());
+            }
+            if upgrade.cost <= BigNum::ZERO {
+                return Err(format!("{} : coût invalide", upgrade.name));
+            }
+            if !upgrade.effect.is_valid() {
+                return Err(format!("{} : effet invalide", upgrade.name));
+            }
+        }
+        if let Some(rules) = &self.rules {
+            rules.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything the game can build, buy and unlock: the base catalogs plus the content packs.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Catalog {
+    pub buildings: Vec<BuildingDef>,
+    pub powerups: Vec<PowerUpDef>,
+    pub achievements: Vec<AchievementDef>,
+    pub heavenly: Vec<HeavenlyDef>,
+    pub rules: Rules,
+}
+
+impl Catalog {
+    pub fn from_packs<'a>(packs: impl IntoIterator<Item = &'a ContentPack>) -> Result<Self, String> {
+        let mut catalog = Catalog::default();
+        for pack in packs {
+            catalog.buildings.extend(pack.buildings.iter().cloned());
+            catalog.powerups.extend(pack.powerups.iter().cloned());
+            catalog.achievements.extend(pack.achievements.iter().cloned());
+            catalog.heavenly.extend(pack.heavenly.iter().cloned());
+            if let Some(rules) = pack.rules {
+                catalog.rules = rules;
+            }
+        }
+        catalog.validate()?;
+        Ok(catalog)
+    }
+
+    /// Checks that span several files: ids are what saves refer to, so they must be unique.
+    fn validate(&self) -> Result<(), String> {
+        if self.buildings.is_empty() || self.powerups.is_empty() {
+            return Err("il faut au moins un bâtiment et un power-up".into());
+        }
+
+        unique_ids("bâtiment", self.buildings.iter().map(|b| &b.id))?;
+        unique_ids("power-up", self.powerups.iter().map(|p| &p.id))?;
+        unique_ids("succès", self.achievements.iter().map(|a| &a.id))?;
+        unique_ids("amélioration céleste", self.heavenly.iter().map(|h| &h.id))?;
+
+        for achievement in &self.achievements {
+            if let AchievementRequirement::BuildingCount(building, _) = &achievement.requirement
+                && !self.buildings.iter().any(|b| &b.id == building)
+            {
+                return Err(format!("{} : bâtiment {:?} inexistant", achievement.id, building));
+            }
+            if let AchievementRequirement::ChallengeCompleted(challenge) = &achievement.requirement
+                && challenge_def(challenge).is_none()
+            {
+                return Err(format!("{} : défi {:?} inexistant", achievement.id, challenge));
+            }
+        }
+        self.validate_heavenly_tree()
+    }
+
+    /// Every parent exists and the tree has no cycle, so each upgrade can be
+    /// reached from a root.
+    fn validate_heavenly_tree(&self) -> Result<(), String> {
+        for upgrade in &self.heavenly {
+            if let Some(parent) = upgrade.parents.iter().find(|p| !self.heavenly.iter().any(|h| &h.id == *p)) {
+                return Err(format!("{} : amélioration céleste {:?} inexistante", upgrade.id, parent));
+            }
+        }
+
+        let mut reachable: HashSet<&String> = HashSet::new();
+        while reachable.len() < self.heavenly.len() {
+            let before = reachable.len();
+            for upgrade in &self.heavenly {
+                if upgrade.parents.iter().all(|p| reachable.contains(p)) {
+                    reachable.insert(&upgrade.id);
+                }
+            }
+            if reachable.len() == before {
+                let stuck = self.heavenly.iter().find(|h| !reachable.contains(&h.id)).map_or("", |h| h.id.as_str());
+                return Err(format!("{} : cycle dans l'arbre céleste", stuck));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn upgrades(&self) -> Vec<Upgrade> {
+        self.buildings.iter()
+            .map(|b| Upgrade {
+                id: b.id.clone(),
+                name: tr_or(&format!("building.{}.name", b.id), &b.name),
+                emoji: b.emoji.clone(),
+                base_cost: b.base_cost,
+                cost: b.base_cost,
+                cps: b.cps,
+                count: 0,
+                description: if b.description.is_empty() {
+                    tr_with("building.default_description", &[("cps", format_decimal(b.cps))])
+                } else {
+                    tr_or(&format!("building.{}.description", b.id), &b.description)
+                },
+                tier: b.tier,
+            })
+            .collect()
+    }
+
+    pub fn powerups(&self) -> Vec<PowerUp> {
+        self.powerups.iter()
+            .map(|p| PowerUp {
+                id: p.id.clone(),
+                name: tr_or(&format!("powerup.{}.name", p.id), &p.name),
+                emoji: p.emoji.clone(),
+                base_cost: p.base_cost,
+                cost: p.base_cost,
+                multiplier: p.multiplier,
+                effect: p.effect,
+                count: 0,
+                description: if p.description.is_empty() {
+                    p.effect.describe(p.multiplier)
+                } else {
+                    tr_or(&format!("powerup.{}.description", p.id), &p.description)
+                },
+            })
+            .collect()
+    }
+
+    pub fn achievements(&self) -> Vec<Achievement> {
+        self.achievements.iter()
+            .map(|a| Achievement {
+                id: a.id.clone(),
+                name: tr_or(&format!("achievement.{}.name", a.id), &a.name),
+                description: if a.description.is_empty() {
+                    self.describe_requirement(&a.requirement)
+                } else {
+                    tr_or(&format!("achievement.{}.description", a.id), &a.description)
+                },
+                emoji: a.emoji.clone(),
+                requirement: a.requirement.clone(),
+            })
+            .collect()
+    }
+
+    pub fn heavenly(&self) -> Vec<HeavenlyUpgrade> {
+        self.heavenly.iter()
+            .map(|h| HeavenlyUpgrade {
+                id: h.id.clone(),
+                name: tr_or(&format!("heavenly.{}.name", h.id), &h.name),
+                emoji: h.emoji.clone(),
+                description: h.effect.describe(),
+                cost: h.cost,
+                parents: h.parents.clone(),
+                effect: h.effect,
+                bought: false,
+            })
+            .collect()
+    }
+
+    /// Default achievement description, in the current language and notation.
+    fn describe_requirement(&self, requirement: &AchievementRequirement) -> String {
+        let count = |key: &str, n: f64, shown: String| tr_count(key, n, &[("count", shown)]);
+        match requirement {
+            AchievementRequirement::TotalCookies(n) => count("requirement.total_cookies", n.to_f64(), format_number(*n)),
+            AchievementRequirement::CookiesPerSecond(n) => count("requirement.cps", n.to_f64(), format_number(*n)),
+            AchievementRequirement::Clicks(n) => count("requirement.clicks", *n as f64, format_number(*n as f64)),
+            AchievementRequirement::GoldenCookies(n) => count("requirement.golden_cookies", *n as f64, format_number(*n as f64)),
+            AchievementRequirement::Combos(n) => count("requirement.combos", *n as f64, format_number(*n as f64)),
+            AchievementRequirement::PrestigeLevel(n) => tr_with("requirement.prestige", &[("level", format_number(*n as f64))]),
+            AchievementRequirement::BuildingCount(id, n) => {
+                let building = self.buildings.iter()
+                    .find(|b| &b.id == id)
+                    .map_or_else(|| id.clone(), |b| tr_or(&format!("building.{}.name", b.id), &b.name));
+                tr_with("requirement.buildings", &[("count", format_number(*n as f64)), ("building", building)])
+            }
+            AchievementRequirement::ChallengeCompleted(id) => {
+                let challenge = challenge_def(id).map_or_else(|| id.clone(), |c| c.name());
+                tr_with("requirement.challenge", &[("challenge", challenge)])
+            }
+        }
+    }
+
+    /// Base catalogs as they were when the game was built.
+    pub fn embedded() -> Self {
+        let packs: Vec<ContentPack> = EMBEDDED_FILES.iter()
+            .map(|text| ContentPack::parse(text).expect("catalogue intégré invalide"))
+            .collect();
+        Catalog::from_packs(&packs).expect("catalogue intégré invalide")
+    }
+
+    /// Reads the same files the asset server watches, so the save loaded at startup
+    /// already knows about every building of the content packs.
+    pub fn load_from_disk(assets: &Path) -> Result<Self, String> {
+        let mut packs = Vec::new();
+        for path in catalog_paths(assets) {
+            let text = fs::read_to_string(assets.join(&path)).map_err(|e| format!("{} : {}", path, e))?;
+            packs.push(ContentPack::parse(&text).map_err(|e| format!("{} : {}", path, e))?);
+        }
+        Catalog::from_packs(&packs)
+    }
+}
+
+/// Ids stay ASCII so they survive any translation of the display names.
+pub fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn unique_ids<'a>(kind: &str, ids: impl Iterator<Item = &'a String>) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for id in ids {
+        if !seen.insert(id) {
+            return Err(format!("{} en double : {}", kind, id));
+        }
+    }
+    Ok(())
+}
+
+/// Base catalogs then content packs, relative to the assets directory.
+fn catalog_paths(assets: &Path) -> Vec<String> {
+    let mut packs: Vec<String> = fs::read_dir(assets.join(PACKS_DIR))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".ron"))
+        .map(|name| format!("{}/{}", PACKS_DIR, name))
+        .collect();
+    packs.sort();
+
+    CATALOG_FILES.iter().map(|path| path.to_string()).chain(packs).collect()
+}
+
+static CURRENT: LazyLock<RwLock<Arc<Catalog>>> = LazyLock::new(|| RwLock::new(Arc::new(Catalog::embedded())));
+
+/// The catalog in use; `get_upgrade()`, `get_powerups()`, `get_achievements()`
+/// and `get_heavenly()` read from it. Cheap to call, the catalog itself is shared.
+pub fn current() -> Arc<Catalog> {
+    Arc::clone(&CURRENT.read().unwrap_or_else(PoisonError::into_inner))
+}
+
+pub fn install(catalog: Catalog) {
+    *CURRENT.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(catalog);
+}
+
+/// Installs the catalogs found next to the executable, or the embedded ones.
+pub fn install_from_disk() {
+    let assets = FileAssetReader::get_base_path().join("assets");
+    match Catalog::load_from_disk(&assets) {
+        Ok(catalog) => {
+            println!(
+                "📦 Catalogue : {} bâtiments, {} power-ups, {} succès, {} améliorations célestes",
+                catalog.buildings.len(), catalog.powerups.len(), catalog.achievements.len(), catalog.heavenly.len()
+            );
+            install(catalog);
+        }
+        Err(e) => {
+            eprintln!("❌ Catalogue illisible, contenu intégré utilisé : {}", e);
+            install(Catalog::embedded());
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ContentPackLoader;
+
+impl AssetLoader for ContentPackLoader {
+    type Asset = ContentPack;
+    type Settings = ();
+    type Error = io::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<ContentPack, io::Error> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text).await?;
+        ContentPack::parse(&text).map_err(io::Error::other)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+/// Handles of every catalog file, in merge order.
+#[derive(Resource)]
+pub struct CatalogHandles(pub Vec<Handle<ContentPack>>);
+
+pub fn load_catalog_assets(mut commands: Commands, assets: Res<AssetServer>) {
+    let base = FileAssetReader::get_base_path().join("assets");
+    let handles = catalog_paths(&base).into_iter().map(|path| assets.load(path)).collect();
+    commands.insert_resource(CatalogHandles(handles));
+}
+
+/// Merges the loaded files into a catalog once they are all there. None while
+/// some are still loading.
+pub fn loaded_catalog(handles: &CatalogHandles, packs: &Assets<ContentPack>) -> Option<Result<Catalog, String>> {
+    let loaded: Option<Vec<&ContentPack>> = handles.0.iter().map(|h| packs.get(h)).collect();
+    loaded.map(Catalog::from_packs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_catalogs_are_valid() {
+        let catalog = Catalog::embedded();
+        assert_eq!(catalog.buildings[0].id, "curseur");
+        assert_eq!(catalog.buildings[0].name, "Curseur");
+        assert_eq!(catalog.upgrades()[1].cost, BigNum::from(100.0));
+        assert_eq!(catalog.powerups()[1].description, "+2 cookies par clic");
+        let achievements = catalog.achievements();
+        let first = achievements.iter().find(|a| a.name == "Premiers pas").unwrap();
+        assert_eq!(first.description, "Cliquez 10 fois");
+    }
+
+    #[test]
+    fn files_on_disk_match_the_embedded_copy() {
+        let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+        assert_eq!(Catalog::load_from_disk(&assets).unwrap(), Catalog::embedded());
+    }
+
+    #[test]
+    fn packs_only_need_the_sections_they_add() {
+        let pack = ContentPack::parse(r#"(buildings: [(id: "moulin", name: "Moulin", base_cost: "2e40", cps: 1e30)])"#).unwrap();
+        let base = Catalog::embedded();
+        let buildings = ContentPack { buildings: base.buildings.clone(), ..ContentPack::default() };
+        let powerups = ContentPack { powerups: base.powerups.clone(), ..ContentPack::default() };
+
+        let catalog = Catalog::from_packs([&buildings, &powerups, &pack]).unwrap();
+        let moulin = catalog.upgrades().pop().unwrap();
+        assert_eq!(moulin.name, "Moulin");
+        assert_eq!(moulin.base_cost, BigNum::new(2.0, 40));
+        assert_eq!(moulin.tier, 0);
+    }
+
+    #[test]
+    fn packs_may_override_rules() {
+        let base = Catalog::embedded();
+        assert_eq!(base.rules, Rules::default());
+
+        let pack = ContentPack::parse("(rules: (offline_max_hours: 8))").unwrap();
+        let content = ContentPack { buildings: base.buildings.clone(), powerups: base.powerups.clone(), ..ContentPack::default() };
+        let catalog = Catalog::from_packs([&content, &pack]).unwrap();
+        assert_eq!(catalog.rules.offline_max_seconds(), 8 * 3_600);
+        assert_eq!(catalog.rules.offline_efficiency, 0.5);
+
+        assert!(ContentPack::parse("(rules: (offline_efficiency: 1.5))").is_err());
+        assert!(ContentPack::parse("(rules: (offline_max_hours: -1))").is_err());
+        assert!(ContentPack::parse("(rules: (sell_refund: 2))").is_err());
+    }
+
+    #[test]
+    fn schema_errors_are_reported() {
+        assert!(ContentPack::parse("(buildings: [(id: \"a\", name: \"A\", base_cost: 10)])").is_err());
+        assert!(ContentPack::parse("(buildings: [(id: \"a\", name: \"A\", base_cost: 10, cps: 1, colour: 3)])").is_err());
+        assert!(ContentPack::parse("(buildings: [(id: \"a\", name: \"A\", base_cost: 0, cps: 1)])").is_err());
+        assert!(ContentPack::parse("(buildings: [(name: \"A\", base_cost: 10, cps: 1)])").is_err());
+        assert!(ContentPack::parse("(buildings: [(id: \"Grand-mère\", name: \"A\", base_cost: 10, cps: 1)])").is_err());
+        assert!(ContentPack::parse("(recipes: [])").is_err());
+    }
+
+    #[test]
+    fn catalog_errors_are_reported() {
+        let base = Catalog::embedded();
+        let mut buildings = ContentPack { buildings: base.buildings.clone(), powerups: base.powerups.clone(), ..ContentPack::default() };
+        assert!(Catalog::from_packs([&buildings]).is_ok());
+
+        let duplicate = ContentPack { buildings: vec![base.buildings[0].clone()], ..ContentPack::default() };
+        assert!(Catalog::from_packs([&buildings, &duplicate]).unwrap_err().contains("curseur"));
+
+        buildings.achievements.push(AchievementDef {
+            id: "fantome".into(),
+            name: "Fantôme".into(),
+            description: String::new(),
+            emoji: String::new(),
+            requirement: AchievementRequirement::BuildingCount("moulin".into(), 1),
+        });
+        assert!(Catalog::from_packs([&buildings]).is_err());
+        assert!(Catalog::from_packs([&ContentPack::default()]).is_err());
+    }
+
+    #[test]
+    fn heavenly_tree_errors_are_reported() {
+        let base = Catalog::embedded();
+        let content = ContentPack { buildings: base.buildings.clone(), powerups: base.powerups.clone(), ..ContentPack::default() };
+        let tree = |text: &str| Catalog::from_packs([&content, &ContentPack::parse(text).unwrap()]);
+
+        assert!(tree(r#"(heavenly: [(id: "a", name: "A", cost: 1, effect: KeepBuildings(1))])"#).is_ok());
+        assert!(tree(r#"(heavenly: [(id: "a", name: "A", cost: 1, parents: ["b"], effect: KeepBuildings(1))])"#)
+            .unwrap_err().contains("\"b\""));
+        assert!(tree(r#"(heavenly: [
+            (id: "a", name: "A", cost: 1, parents: ["b"], effect: KeepBuildings(1)),
+            (id: "b", name: "B", cost: 1, parents: ["a"], effect: KeepBuildings(1)),
+        ])"#).unwrap_err().contains("cycle"));
+        assert!(ContentPack::parse(r#"(heavenly: [(id: "a", name: "A", cost: 1, effect: OfflineEfficiency(2))])"#).is_err());
+        assert!(ContentPack::parse(r#"(heavenly: [(id: "a", name: "A", cost: 1, effect: GoldenFrequency(0))])"#).is_err());
+    }
+}
@@ -0,0 +1,307 @@
+use crate::bignum::BigNum;
+use crate::locale::{tr, tr_with};
+use crate::resources::GameState;
+use crate::utils::{format_decimal, format_number};
+
+/// Permanent bonus a challenge grants the first time it is completed.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ChallengeReward {
+    /// Multiplies the production of every run.
+    Production(f64),
+    /// Multiplies the cookies per click.
+    Click(f64),
+    /// Golden cookies show up this many times as often.
+    GoldenFrequency(f64),
+}
+
+/// A run played under restrictions until it bakes `goal` cookies. Chosen when
+/// prestiging; the restrictions lift as soon as the goal is met.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ChallengeDef {
+    pub id: &'static str,
+    pub emoji: &'static str,
+    /// The big cookie does nothing.
+    pub no_clicks: bool,
+    /// Buildings above this tier cannot be bought.
+    pub max_tier: Option<u128>,
+    /// Golden cookies never show up.
+    pub no_golden: bool,
+    /// Cookies the run must bake.
+    pub goal: f64,
+    /// Bank the run starts with at least, so a run without clicks can buy its first building.
+    pub bank: f64,
+    pub reward: ChallengeReward,
+}
+
+pub const SANS_CLIC: ChallengeDef = ChallengeDef {
+    id: "sans_clic",
+    emoji: "🚫",
+    no_clicks: true,
+    max_tier: None,
+    no_golden: false,
+    goal: 1e9,
+    bank: 15.0,
+    reward: ChallengeReward::Production(1.1),
+};
+
+pub const AGE_DE_PIERRE: ChallengeDef = ChallengeDef {
+    id: "age_de_pierre",
+    emoji: "🪨",
+    no_clicks: false,
+    max_tier: Some(0),
+    no_golden: false,
+    goal: 1e12,
+    bank: 0.0,
+    reward: ChallengeReward::Click(2.0),
+};
+
+pub const SANS_OR: ChallengeDef = ChallengeDef {
+    id: "sans_or",
+    emoji: "🌑",
+    no_clicks: false,
+    max_tier: None,
+    no_golden: true,
+    goal: 1e10,
+    bank: 0.0,
+    reward: ChallengeReward::GoldenFrequency(1.2),
+};
+
+pub const ASCETE: ChallengeDef = ChallengeDef {
+    id: "ascete",
+    emoji: "🧘",
+    no_clicks: true,
+    max_tier: Some(0),
+    no_golden: true,
+    goal: 1e12,
+    bank: 15.0,
+    reward: ChallengeReward::Production(1.25),
+};
+
+pub const CHALLENGES: [ChallengeDef; 4] = [SANS_CLIC, AGE_DE_PIERRE, SANS_OR, ASCETE];
+
+pub fn challenge_def(id: &str) -> Option<&'static ChallengeDef> {
+    CHALLENGES.iter().find(|c| c.id == id)
+}
+
+impl ChallengeDef {
+    pub fn name(&self) -> String {
+        tr(&format!("challenge.{}.name", self.id))
+    }
+
+    /// Restrictions, goal and reward, one per line.
+    pub fn description(&self) -> String {
+        let mut lines = Vec::new();
+        if self.no_clicks {
+            lines.push(tr("challenge.rule.no_clicks"));
+        }
+        if let Some(tier) = self.max_tier {
+            lines.push(tr_with("challenge.rule.max_tier", &[("tier", tier.to_string())]));
+        }
+        if self.no_golden {
+            lines.push(tr("challenge.rule.no_golden"));
+        }
+        lines.push(tr_with("challenge.goal", &[("count", format_number(self.goal))]));
+        lines.push(self.reward_description());
+        lines.join("\n")
+    }
+
+    pub fn reward_description(&self) -> String {
+        let (key, factor) = match self.reward {
+            ChallengeReward::Production(factor) => ("challenge.reward.production", factor),
+            ChallengeReward::Click(factor) => ("challenge.reward.click", factor),
+            ChallengeReward::GoldenFrequency(factor) => ("challenge.reward.golden", factor),
+        };
+        tr_with(key, &[("multiplier", format_decimal(factor))])
+    }
+}
+
+impl GameState {
+    /// Definition of the challenge being played, if any.
+    pub fn active_challenge(&self) -> Option<&'static ChallengeDef> {
+        self.challenge.as_deref().and_then(challenge_def)
+    }
+
+    pub fn can_click(&self) -> bool {
+        !self.active_challenge().is_some_and(|c| c.no_clicks)
+    }
+
+    pub fn building_allowed(&self, index: usize) -> bool {
+        let max_tier = self.active_challenge().and_then(|c| c.max_tier);
+        self.upgrades.get(index).is_some_and(|u| max_tier.is_none_or(|tier| u.tier <= tier))
+    }
+
+    pub fn golden_allowed(&self) -> bool {
+        !self.active_challenge().is_some_and(|c| c.no_golden)
+    }
+
+    /// Starts challenge `id` on the run that was just reset.
+    pub fn start_challenge(&mut self, id: &str) -> bool {
+        let Some(def) = challenge_def(id) else {
+            return false;
+        };
+        self.challenge = Some(def.id.to_string());
+        // Buildings kept by heavenly upgrades must not get around the tier limit.
+        if let Some(max_tier) = def.max_tier {
+            for upgrade in self.upgrades.iter_mut().filter(|u| u.tier > max_tier) {
+                upgrade.count = 0;
+                upgrade.cost = upgrade.base_cost;
+            }
+        }
+        let bank = BigNum::from(def.bank);
+        if self.cookies < bank {
+            self.cookies = bank;
+        }
+        self.refresh_production();
+        true
+    }
+
+    /// Ends the challenge once its goal is baked, returning it. Its reward only
+    /// counts the first time.
+    pub fn check_challenge(&mut self) -> Option<&'static ChallengeDef> {
+        let def = self.active_challenge()?;
+        if self.total_cookies_earned < BigNum::from(def.goal) {
+            return None;
+        }
+        self.challenge = None;
+        if !self.challenge_completed(def.id) {
+            self.challenges_completed.push(def.id.to_string());
+        }
+        self.refresh_production();
+        Some(def)
+    }
+
+    pub fn challenge_completed(&self, id: &str) -> bool {
+        self.challenges_completed.iter().any(|c| c == id)
+    }
+
+    /// How far the run is from the goal of its challenge, from 0 to 1.
+    pub fn challenge_progress(&self) -> f32 {
+        self.active_challenge().map_or(0.0, |def| {
+            (self.total_cookies_earned / def.goal).to_f64().clamp(0.0, 1.0) as f32
+        })
+    }
+
+    fn challenge_rewards(&self) -> impl Iterator<Item = ChallengeReward> + '_ {
+        self.challenges_completed.iter()
+            .filter_map(|id| challenge_def(id))
+            .map(|def| def.reward)
+    }
+
+    /// Production multiplier earned by completed challenges.
+    pub fn challenge_production(&self) -> f64 {
+        self.challenge_rewards()
+            .filter_map(|r| match r {
+                ChallengeReward::Production(factor) => Some(factor),
+                _ => None,
+            })
+            .product()
+    }
+
+    /// Click multiplier earned by completed challenges.
+    pub fn challenge_click(&self) -> f64 {
+        self.challenge_rewards()
+            .filter_map(|r| match r {
+                ChallengeReward::Click(factor) => Some(factor),
+                _ => None,
+            })
+            .product()
+    }
+
+    /// Golden cookie frequency multiplier earned by completed challenges.
+    pub fn challenge_golden_frequency(&self) -> f64 {
+        self.challenge_rewards()
+            .filter_map(|r| match r {
+                ChallengeReward::GoldenFrequency(factor) => Some(factor),
+                _ => None,
+            })
+            .product()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::BuyAmount;
+
+    fn n(value: f64) -> BigNum {
+        BigNum::from(value)
+    }
+
+    #[test]
+    fn restrictions_follow_the_challenge() {
+        let mut state = GameState::default();
+        let high_tier = state.upgrades.iter().position(|u| u.tier > 0).unwrap();
+        assert!(state.can_click() && state.golden_allowed() && state.building_allowed(high_tier));
+
+        assert!(state.start_challenge("ascete"));
+        assert!(!state.can_click());
+        assert!(!state.golden_allowed());
+        assert!(state.building_allowed(0));
+        assert!(!state.building_allowed(high_tier));
+        assert_eq!(state.cookies, n(15.0));
+
+        assert_eq!(state.click(0, 0.0).earned, BigNum::ZERO);
+        assert_eq!(state.click_count, 0);
+
+        state.cookies = n(1e30);
+        assert!(!state.buy_building(high_tier, BuyAmount::One));
+        assert!(state.buy_building(0, BuyAmount::One));
+        assert!(!state.start_challenge("inconnu"));
+    }
+
+    #[test]
+    fn completing_grants_the_reward_once() {
+        let mut state = GameState::default();
+        state.upgrades[0].count = 10;
+        state.refresh_production();
+        let cps = state.cookies_per_second;
+
+        state.start_challenge("sans_clic");
+        state.total_cookies_earned = n(9e8);
+        assert_eq!(state.check_challenge(), None);
+        assert_eq!(state.challenge_progress(), 0.9);
+
+        state.total_cookies_earned = n(1e9);
+        assert_eq!(state.check_challenge(), Some(&SANS_CLIC));
+        assert_eq!(state.challenge, None);
+        assert!(state.can_click());
+        assert_eq!(state.cookies_per_second, cps * 1.1);
+
+        state.start_challenge("sans_clic");
+        state.check_challenge();
+        assert_eq!(state.challenges_completed, vec!["sans_clic".to_string()]);
+        assert_eq!(state.challenge_production(), 1.1);
+    }
+
+    #[test]
+    fn kept_buildings_respect_the_tier_limit() {
+        let mut state = GameState { prestige_points: n(1e12), ..GameState::default() };
+        for id in ["benediction", "heritage"] {
+            let index = state.heavenly_index(id).unwrap();
+            assert!(state.buy_heavenly(index));
+        }
+        let high_tier = state.upgrades.iter().position(|u| u.tier > 0).unwrap();
+        state.upgrades[0].count = 20;
+        state.upgrades[high_tier].count = 20;
+        state.total_cookies_earned = state.prestige_cost();
+        assert!(state.do_prestige());
+        assert_eq!(state.upgrades[high_tier].count, 10);
+
+        assert!(state.start_challenge("age_de_pierre"));
+        assert_eq!(state.upgrades[0].count, 10);
+        assert_eq!(state.upgrades[high_tier].count, 0);
+        assert_eq!(state.upgrades[high_tier].cost, state.upgrades[high_tier].base_cost);
+        assert_eq!(state.upgrades.iter().filter(|u| u.count > 0).count(), 1);
+        assert!(state.cookies_per_second > BigNum::ZERO);
+    }
+
+    #[test]
+    fn prestige_ends_the_challenge() {
+        let mut state = GameState::default();
+        state.start_challenge("sans_or");
+        state.total_cookies_earned = state.prestige_cost();
+        assert!(state.do_prestige());
+        assert_eq!(state.challenge, None);
+        assert!(state.golden_allowed());
+    }
+}
@@ -1,4 +1,6 @@
 use bevy::prelude::*;
+use crate::resources::BuyAmount;
+use crate::settings::Setting;
 
 #[derive(Component)]
 pub struct Cookie;
@@ -14,42 +16,125 @@ pub struct StatsText;
 
 #[derive(Component)]
 pub struct UpgradeButton {
-    pub upgrade_index: usize,
+    pub building: String,
 }
 
 #[derive(Component)]
 pub struct UpgradeText {
-    pub upgrade_index: usize,
+    pub building: String,
 }
 
 #[derive(Component)]
 pub struct PowerUpButton {
-    pub powerup_index: usize,
+    pub powerup: String,
 }
 
 #[derive(Component)]
 pub struct PowerUpText {
-    pub powerup_index: usize,
+    pub powerup: String,
+}
+
+#[derive(Component)]
+pub struct ResearchButton {
+    pub research: String,
+}
+
+#[derive(Component)]
+pub struct ResearchText {
+    pub research: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ShopSection {
+    PowerUps,
+    Research,
+    Buildings,
+}
+
+/// Scrolling list holding the buttons of one shop section.
+#[derive(Component)]
+pub struct ShopList(pub ShopSection);
+
+/// Title of a shop section, which also shows how many entries it lists.
+#[derive(Component)]
+pub struct ShopSectionTitle(pub ShopSection);
+
+/// Fixed label, rendered again from its key whenever the language changes.
+#[derive(Component)]
+pub struct LocalizedText {
+    pub key: &'static str,
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl LocalizedText {
+    pub fn new(key: &'static str) -> Self {
+        Self { key, args: Vec::new() }
+    }
+
+    pub fn with(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+}
+
+/// Opens the settings menu, or closes it from inside.
+#[derive(Component)]
+pub struct SettingsButton;
+
+#[derive(Component)]
+pub struct SettingsMenu;
+
+#[derive(Component)]
+pub struct SettingButton {
+    pub setting: Setting,
+}
+
+/// Current value of a setting, next to its name in the menu.
+#[derive(Component)]
+pub struct SettingValue(pub Setting);
+
+#[derive(Component)]
+pub struct VolumeSlider {
+    pub setting: Setting,
+}
+
+/// Filled part of a volume slider.
+#[derive(Component)]
+pub struct SliderFill(pub Setting);
+
+#[derive(Component)]
+pub struct BuyAmountButton {
+    pub amount: BuyAmount,
 }
 
 #[derive(Component)]
 pub struct PopupText {
     pub lifetime: Timer,
     pub velocity: Vec2,
+    pub font_size: f32,
 }
 
 #[derive(Component)]
 pub struct CookieScale {
-    pub base_scale: f32,
     pub pulse: f32,
 }
 
 #[derive(Component)]
 pub struct GoldenCookie {
     pub lifetime: Timer,
-    pub multiplier: u64,
 }
 
+/// Column listing the running buffs, rebuilt when the list changes.
+#[derive(Component)]
+pub struct BuffBar;
+
+/// Remaining-time bar of the buff at this index of `GameState::buffs`.
+#[derive(Component)]
+pub struct BuffFill(pub usize);
+
+#[derive(Component)]
+pub struct BuffTimeText(pub usize);
+
 #[derive(Component)]
 pub struct ComboText;
 
@@ -62,6 +147,79 @@ pub struct PrestigeButton;
 #[derive(Component)]
 pub struct PrestigeText;
 
+/// Fill of the bar showing how close the run is to its next prestige level.
+#[derive(Component)]
+pub struct PrestigeFill;
+
+/// Challenge being played and how close its goal is, empty outside challenges.
+#[derive(Component)]
+pub struct ChallengeText;
+
+/// Picks the challenge of the next run in the prestige dialog; `None` is a normal run.
+#[derive(Component)]
+pub struct ChallengeChoiceButton {
+    pub challenge: Option<String>,
+}
+
+/// Rules of the challenge picked in the prestige dialog.
+#[derive(Component)]
+pub struct ChallengeDescriptionText;
+
+/// Opens the heavenly upgrade tree, or closes it from inside.
+#[derive(Component)]
+pub struct HeavenlyButton;
+
+#[derive(Component)]
+pub struct HeavenlyMenu;
+
+/// Balance of heavenly chips shown above the tree.
+#[derive(Component)]
+pub struct HeavenlyChipsText;
+
+#[derive(Component)]
+pub struct HeavenlyNodeButton {
+    pub upgrade: String,
+}
+
+#[derive(Component)]
+pub struct HeavenlyNodeText {
+    pub upgrade: String,
+}
+
+#[derive(Component)]
+pub struct SaveSlotButton {
+    pub slot: usize,
+}
+
+#[derive(Component, Clone, Copy, PartialEq)]
+pub enum SaveTransferButton {
+    Export,
+    Import,
+}
+
+#[derive(Component)]
+pub struct ConfirmDialog;
+
+/// What confirming a dialog does.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DialogAction {
+    Import,
+    Prestige,
+}
+
+#[derive(Component)]
+pub struct DialogButton {
+    /// `None` on the cancel button.
+    pub confirms: Option<DialogAction>,
+}
+
+/// Shown after a prestige: the run that just ended and the ones before it.
+#[derive(Component)]
+pub struct RunSummaryScreen;
+
+#[derive(Component)]
+pub struct RunSummaryButton;
+
 #[derive(Component)]
 pub struct Particle {
     pub lifetime: Timer,
@@ -76,14 +234,7 @@ pub struct ScrollingList {
     pub(crate) position: f32,
 }
 
-#[derive(Component)]
-pub struct PowerUpScrollArea;
-
-#[derive(Component)]
-pub struct BuildingScrollArea;
-
 #[derive(Component)]
 pub struct AchievementPopup {
     pub timer: Timer,
-    pub index: usize,
 }
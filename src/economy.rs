@@ -0,0 +1,798 @@
+use crate::bignum::BigNum;
+use crate::buffs::BuffTarget;
+use crate::research::ResearchEffect;
+use crate::resources::{BuyAmount, GameState};
+use crate::powerups::PowerUpEffect;
+use crate::{powerups, upgrades};
+
+/// Production bonus per prestige level.
+pub const PRESTIGE_BUFF: f64 = 0.01;
+
+/// Cookies a run must bake for a prestige worth one level; `n` levels take `n³` times as many.
+const PRESTIGE_COOKIES_PER_LEVEL: f64 = 1e12;
+/// Cookies baked during a run per heavenly chip its prestige grants.
+const COOKIES_PER_CHIP: f64 = 1e6;
+
+/// Cookies a run must bake for a prestige worth `levels` levels.
+pub fn prestige_requirement(levels: u64) -> BigNum {
+    BigNum::from(PRESTIGE_COOKIES_PER_LEVEL) * BigNum::from(levels as f64).powf(3.0)
+}
+
+/// Levels a run that baked `earned` cookies is worth, before rounding down: the
+/// cube root of the earnings counted in `PRESTIGE_COOKIES_PER_LEVEL`.
+pub fn prestige_levels(earned: BigNum) -> f64 {
+    (earned / PRESTIGE_COOKIES_PER_LEVEL).to_f64().cbrt()
+}
+
+/// Cookies a click earned and whether it was a critical one.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ClickOutcome {
+    pub earned: BigNum,
+    pub critical: bool,
+}
+
+/// CPS and click power as derived from the rest of the state.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Production {
+    pub cookies_per_second: BigNum,
+    pub cookies_per_click: BigNum,
+}
+
+/// The only place production is computed: buildings boosted by research, buffs
+/// and prestige for CPS, one cookie plus the power-ups and their share of the CPS,
+/// times the click buffs, for a click. Every change to the inputs goes through `GameState::refresh_production`
+/// so the order of events never matters.
+pub fn recompute_production(state: &GameState) -> Production {
+    let multipliers = building_multipliers(state);
+    let buildings: BigNum = state.upgrades.iter()
+        .zip(multipliers)
+        .map(|(upgrade, multiplier)| upgrade.cps * (upgrade.count as f64 * multiplier))
+        .sum();
+    let powerups: BigNum = state.powerups.iter()
+        .filter(|powerup| powerup.effect == PowerUpEffect::Flat)
+        .map(|powerup| powerup.multiplier * powerup.count as f64)
+        .sum();
+
+    let production = state.buffs.multiplier(&BuffTarget::Production);
+    let click = state.buffs.multiplier(&BuffTarget::Click);
+    let cookies_per_second = buildings * (state.prestige_multiplier() * production * state.challenge_production());
+
+    Production {
+        cookies_per_second,
+        cookies_per_click: (BigNum::ONE + powerups + cookies_per_second * state.cps_share())
+            * (click * state.challenge_click()),
+    }
+}
+
+/// Output multiplier of each building from research and the buffs aimed at it.
+fn building_multipliers(state: &GameState) -> Vec<f64> {
+    research_multipliers(state).into_iter()
+        .zip(&state.upgrades)
+        .map(|(multiplier, upgrade)| {
+            multiplier * state.buffs.multiplier(&BuffTarget::Building(upgrade.id.clone()))
+        })
+        .collect()
+}
+
+/// Output multiplier of each building from the research bought so far.
+fn research_multipliers(state: &GameState) -> Vec<f64> {
+    let mut multipliers = vec![1.0; state.upgrades.len()];
+    for research in state.research.iter().filter(|r| r.bought) {
+        let (target, factor) = match &research.effect {
+            ResearchEffect::Multiplier(target, factor) => (target, *factor),
+            ResearchEffect::Synergy(source, target, bonus) => {
+                let count = state.building(source).map_or(0, |u| u.count);
+                (target, 1.0 + bonus * count as f64)
+            }
+        };
+        if let Some(multiplier) = state.building_index(target).and_then(|i| multipliers.get_mut(i)) {
+            *multiplier *= factor;
+        }
+    }
+    multipliers
+}
+
+/// Total price of `quantity` units when the next one costs `cost` and each
+/// following one `growth` times more.
+pub fn bulk_cost(cost: BigNum, growth: f64, quantity: u64) -> BigNum {
+    match quantity {
+        0 => BigNum::ZERO,
+        1 => cost,
+        _ => {
+            let growth = BigNum::from(growth);
+            // Without growth the series is just `quantity` times the price.
+            match (growth.powf(quantity as f64) - BigNum::ONE).checked_div(growth - BigNum::ONE) {
+                Some(factor) => (cost * factor).floor(),
+                None => cost * quantity as f64,
+            }
+        }
+    }
+}
+
+/// Largest quantity whose `bulk_cost` fits in `bank`.
+pub fn max_affordable(cost: BigNum, growth: f64, bank: BigNum) -> u64 {
+    if cost.is_zero() || bank < cost {
+        return 0;
+    }
+
+    // Inverse of the geometric series, then nudged to absorb rounding.
+    let estimate = ((bank / cost) * (growth - 1.0) + BigNum::ONE).log10() / growth.log10();
+    let mut quantity = (estimate.floor() as u64).max(1);
+    while quantity > 1 && bulk_cost(cost, growth, quantity) > bank {
+        quantity -= 1;
+    }
+    while bulk_cost(cost, growth, quantity + 1) <= bank {
+        quantity += 1;
+    }
+    quantity
+}
+
+impl BuyAmount {
+    /// Units this amount stands for. `Max` never goes below one so the price of
+    /// the next unit is still shown when nothing is affordable.
+    pub fn quantity(self, cost: BigNum, growth: f64, bank: BigNum) -> u64 {
+        match self {
+            BuyAmount::One => 1,
+            BuyAmount::Ten => 10,
+            BuyAmount::Hundred => 100,
+            BuyAmount::Max => max_affordable(cost, growth, bank).max(1),
+        }
+    }
+}
+
+impl GameState {
+    pub fn prestige_multiplier(&self) -> f64 {
+        1.0 + (self.prestige_level as f64 * PRESTIGE_BUFF)
+    }
+
+    pub fn building_index(&self, id: &str) -> Option<usize> {
+        self.upgrades.iter().position(|u| u.id == id)
+    }
+
+    pub fn building(&self, id: &str) -> Option<&upgrades::Upgrade> {
+        self.upgrades.iter().find(|u| u.id == id)
+    }
+
+    pub fn powerup_index(&self, id: &str) -> Option<usize> {
+        self.powerups.iter().position(|p| p.id == id)
+    }
+
+    pub fn research_index(&self, id: &str) -> Option<usize> {
+        self.research.iter().position(|r| r.id == id)
+    }
+
+    pub fn refresh_production(&mut self) {
+        let production = recompute_production(self);
+        self.cookies_per_second = production.cookies_per_second;
+        self.cookies_per_click = production.cookies_per_click;
+    }
+
+    /// Current output of one unit of building `index`, research, buffs and prestige included.
+    pub fn building_cps(&self, index: usize) -> BigNum {
+        let multiplier = building_multipliers(self).get(index).copied().unwrap_or(0.0)
+            * self.buffs.multiplier(&BuffTarget::Production)
+            * self.challenge_production();
+        self.upgrades.get(index).map_or(BigNum::ZERO, |upgrade| {
+            upgrade.cps * (multiplier * self.prestige_multiplier())
+        })
+    }
+
+    /// Research not bought yet whose building requirement is met.
+    pub fn research_available(&self, index: usize) -> bool {
+        self.research.get(index).is_some_and(|research| {
+            let (building, count) = &research.requirement;
+            !research.bought && self.building(building).is_some_and(|u| u.count >= *count)
+        })
+    }
+
+    pub fn buy_research(&mut self, index: usize) -> bool {
+        if !self.research_available(index) || self.cookies < self.research[index].cost {
+            return false;
+        }
+
+        self.cookies -= self.research[index].cost;
+        self.research[index].bought = true;
+        self.refresh_production();
+        true
+    }
+
+    /// Adds freshly baked cookies to the bank and to the run/lifetime totals.
+    pub fn earn(&mut self, amount: BigNum) {
+        self.cookies += amount;
+        self.total_cookies_earned += amount;
+        self.lifetime_cookies += amount;
+    }
+
+    /// Units and total price of the next `amount` of building `index`.
+    pub fn building_order(&self, index: usize, amount: BuyAmount) -> Option<(u64, BigNum)> {
+        let upgrade = self.upgrades.get(index)?;
+        let quantity = amount.quantity(upgrade.cost, upgrades::COST_GROWTH, self.cookies);
+        Some((quantity, bulk_cost(upgrade.cost, upgrades::COST_GROWTH, quantity)))
+    }
+
+    /// Units and total price of the next `amount` of power-up `index`.
+    pub fn powerup_order(&self, index: usize, amount: BuyAmount) -> Option<(u64, BigNum)> {
+        let powerup = self.powerups.get(index)?;
+        let quantity = amount.quantity(powerup.cost, powerups::COST_GROWTH, self.cookies);
+        Some((quantity, bulk_cost(powerup.cost, powerups::COST_GROWTH, quantity)))
+    }
+
+    /// Buys the whole order or nothing. Challenges can forbid some buildings.
+    pub fn buy_building(&mut self, index: usize, amount: BuyAmount) -> bool {
+        let Some((quantity, price)) = self.building_order(index, amount) else {
+            return false;
+        };
+        if self.cookies < price || !self.building_allowed(index) {
+            return false;
+        }
+
+        let upgrade = &mut self.upgrades[index];
+        self.cookies -= price;
+        upgrade.count += quantity as u128;
+        upgrade.cost = upgrade.calculate_cost();
+        self.refresh_production();
+        true
+    }
+
+    /// Sells one unit of building `index` for `refund` times what it last cost.
+    /// Returns the cookies given back, or None if there is nothing to sell.
+    pub fn sell_building(&mut self, index: usize, refund: f64) -> Option<BigNum> {
+        let upgrade = self.upgrades.get_mut(index).filter(|u| u.count > 0)?;
+
+        upgrade.count -= 1;
+        upgrade.cost = upgrade.calculate_cost();
+        let cookies = (upgrade.cost * refund).floor();
+        self.cookies += cookies;
+        self.refresh_production();
+        Some(cookies)
+    }
+
+    /// Buys the whole order or nothing.
+    pub fn buy_powerup(&mut self, index: usize, amount: BuyAmount) -> bool {
+        let Some((quantity, price)) = self.powerup_order(index, amount) else {
+            return false;
+        };
+        if self.cookies < price {
+            return false;
+        }
+
+        let powerup = &mut self.powerups[index];
+        self.cookies -= price;
+        powerup.count += quantity as u128;
+        powerup.cost = powerup.calculate_cost();
+        self.refresh_production();
+        true
+    }
+
+    /// Levels prestiging now would grant, from what this run baked. Spending
+    /// cookies never takes them back.
+    pub fn prestige_gain(&self) -> u128 {
+        let mut levels = prestige_levels(self.total_cookies_earned).floor() as u64;
+        // The cube root can land a hair off an exact cube: settle on the requirement.
+        while levels > 0 && prestige_requirement(levels) > self.total_cookies_earned {
+            levels -= 1;
+        }
+        while prestige_requirement(levels + 1) <= self.total_cookies_earned {
+            levels += 1;
+        }
+        levels as u128
+    }
+
+    /// Heavenly chips prestiging now would grant.
+    pub fn prestige_chips(&self) -> BigNum {
+        (self.total_cookies_earned / COOKIES_PER_CHIP).floor()
+    }
+
+    /// Run earnings at which the prestige gain goes up by one.
+    pub fn prestige_cost(&self) -> BigNum {
+        prestige_requirement(self.prestige_gain() as u64 + 1)
+    }
+
+    /// How far the run is from the current gain to the next level, from 0 to 1.
+    pub fn prestige_progress(&self) -> f32 {
+        let gain = self.prestige_gain() as u64;
+        let from = prestige_requirement(gain);
+        let to = prestige_requirement(gain + 1);
+        // Past f64 precision two levels can round to the same requirement.
+        (self.total_cookies_earned - from)
+            .checked_div(to - from)
+            .map_or(0.0, |progress| progress.to_f64().clamp(0.0, 1.0) as f32)
+    }
+
+    /// Seconds until `prestige_cost` at the current production, if there is any.
+    pub fn prestige_eta(&self) -> Option<u64> {
+        let missing = self.prestige_cost() - self.total_cookies_earned;
+        let seconds = missing.checked_div(self.cookies_per_second)?;
+        Some(seconds.to_f64().ceil().min(u64::MAX as f64) as u64)
+    }
+
+    pub fn can_prestige(&self) -> bool {
+        self.prestige_gain() > 0
+    }
+
+    /// Resets the run in exchange for prestige levels and heavenly chips, both
+    /// worth what the run baked. Returns false if it did not bake enough.
+    /// Heavenly upgrades decide what the new run starts with. A challenge left
+    /// unfinished is given up.
+    pub fn do_prestige(&mut self) -> bool {
+        if !self.can_prestige() {
+            return false;
+        }
+
+        self.prestige_level += self.prestige_gain();
+        self.prestige_points += self.prestige_chips();
+        self.cookies = self.starting_cookies();
+        self.total_cookies_earned = BigNum::ZERO;
+        self.combo = 0;
+
+        let kept = self.kept_buildings();
+        for upgrade in &mut self.upgrades {
+            upgrade.count = upgrade.count.min(kept);
+            upgrade.cost = if upgrade.count > 0 { upgrade.calculate_cost() } else { upgrade.base_cost };
+        }
+
+        for powerup in &mut self.powerups {
+            powerup.count = 0;
+            powerup.cost = powerup.base_cost;
+        }
+
+        for research in &mut self.research {
+            research.bought = false;
+        }
+        self.buffs.clear();
+        self.challenge = None;
+        self.refresh_production();
+        true
+    }
+
+    /// Passive production over `dt` seconds. Cookies are fractional, the UI rounds down.
+    pub fn tick(&mut self, dt: f64) -> BigNum {
+        let earned = self.cookies_per_second * dt;
+        if earned > BigNum::ZERO {
+            self.earn(earned);
+        }
+        earned
+    }
+
+    /// One cookie click at combo level `combo` (0 outside a combo). `roll`, in
+    /// [0, 1), decides whether it is critical. Nothing during a challenge without
+    /// clicks.
+    pub fn click(&mut self, combo: u128, roll: f32) -> ClickOutcome {
+        if !self.can_click() {
+            return ClickOutcome { earned: BigNum::ZERO, critical: false };
+        }
+        let critical = (roll as f64) < self.crit_chance();
+        let mut multiplier = (1 + combo) as f64 * self.streak_multiplier(combo);
+        if critical {
+            multiplier *= self.crit_multiplier();
+        }
+
+        let earned = self.cookies_per_click * multiplier;
+        self.earn(earned);
+        self.click_count += 1;
+        self.run.clicks += 1;
+        ClickOutcome { earned, critical }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SELL_REFUND: f64 = 0.5;
+
+    fn n(value: f64) -> BigNum {
+        BigNum::from(value)
+    }
+
+    fn new_game() -> GameState {
+        GameState::default()
+    }
+
+    fn assert_derived(state: &GameState) {
+        let production = recompute_production(state);
+        assert_eq!(state.cookies_per_second, production.cookies_per_second);
+        assert_eq!(state.cookies_per_click, production.cookies_per_click);
+    }
+
+    #[test]
+    fn building_cost_grows_by_fifteen_percent() {
+        let mut state = new_game();
+        let upgrade = &mut state.upgrades[1];
+        assert_eq!(upgrade.calculate_cost(), n(100.0));
+        upgrade.count = 1;
+        assert_eq!(upgrade.calculate_cost(), n(115.0));
+        upgrade.count = 10;
+        assert_eq!(upgrade.calculate_cost(), n(404.0));
+    }
+
+    #[test]
+    fn tier_discounts_building_cost() {
+        let state = new_game();
+        let tiered = state.upgrades.iter().find(|u| u.tier == 1).unwrap();
+        assert_eq!(tiered.calculate_cost(), (tiered.base_cost * 0.99).floor());
+    }
+
+    #[test]
+    fn powerup_cost_grows_by_twenty_percent() {
+        let mut state = new_game();
+        let powerup = &mut state.powerups[0];
+        assert_eq!(powerup.calculate_cost(), n(100.0));
+        powerup.count = 2;
+        assert_eq!(powerup.calculate_cost(), n(144.0));
+        powerup.count = 3;
+        assert_eq!(powerup.calculate_cost(), n(172.0));
+    }
+
+    #[test]
+    fn late_buildings_do_not_overflow() {
+        let mut state = new_game();
+        let last = state.upgrades.last_mut().unwrap();
+        last.count = 10_000;
+        let cost = last.calculate_cost();
+        assert!(cost.exponent() > 600);
+        assert!(cost > n(f64::MAX));
+    }
+
+    #[test]
+    fn buying_a_building_spends_and_adds_cps() {
+        let mut state = new_game();
+        state.cookies = n(150.0);
+
+        assert!(state.buy_building(1, BuyAmount::One));
+        assert_eq!(state.cookies, n(50.0));
+        assert_eq!(state.upgrades[1].count, 1);
+        assert_eq!(state.upgrades[1].cost, n(115.0));
+        assert_eq!(state.cookies_per_second, n(1.0));
+
+        assert!(!state.buy_building(1, BuyAmount::One));
+        assert_eq!(state.cookies, n(50.0));
+        assert_eq!(state.upgrades[1].count, 1);
+    }
+
+    #[test]
+    fn building_cps_includes_prestige_multiplier() {
+        let mut state = new_game();
+        state.prestige_level = 10;
+        state.cookies = n(100.0);
+
+        assert!(state.buy_building(1, BuyAmount::One));
+        assert!((state.cookies_per_second.to_f64() - 1.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn buying_a_powerup_adds_click_power() {
+        let mut state = new_game();
+        state.cookies = n(600.0);
+
+        assert!(state.buy_powerup(1, BuyAmount::One));
+        assert_eq!(state.cookies, n(100.0));
+        assert_eq!(state.cookies_per_click, n(3.0));
+        assert_eq!(state.powerups[1].count, 1);
+        assert_eq!(state.powerups[1].cost, n(600.0));
+    }
+
+    #[test]
+    fn out_of_range_purchases_are_refused() {
+        let mut state = new_game();
+        state.cookies = BigNum::new(1.0, 1_000);
+        assert!(!state.buy_building(usize::MAX, BuyAmount::One));
+        assert!(!state.buy_powerup(state.powerups.len(), BuyAmount::Max));
+        assert_eq!(state.cookies, BigNum::new(1.0, 1_000));
+    }
+
+    #[test]
+    fn bulk_cost_is_a_geometric_series() {
+        let cost = n(100.0);
+        assert_eq!(bulk_cost(cost, 1.15, 0), BigNum::ZERO);
+        assert_eq!(bulk_cost(cost, 1.15, 1), cost);
+
+        let summed: f64 = (0..10).map(|k| 100.0 * 1.15f64.powi(k)).sum();
+        assert_eq!(bulk_cost(cost, 1.15, 10), n(summed.floor()));
+        assert!(bulk_cost(cost, 1.15, 100_000).exponent() > 6_000);
+    }
+
+    #[test]
+    fn max_affordable_fits_the_bank() {
+        assert_eq!(max_affordable(n(100.0), 1.15, n(99.0)), 0);
+        assert_eq!(max_affordable(n(100.0), 1.15, n(100.0)), 1);
+
+        for bank in [n(1e3), n(2_031.0), n(1e12), BigNum::new(4.2, 900)] {
+            let quantity = max_affordable(n(15.0), 1.15, bank);
+            assert!(bulk_cost(n(15.0), 1.15, quantity) <= bank);
+            assert!(bulk_cost(n(15.0), 1.15, quantity + 1) > bank);
+        }
+    }
+
+    #[test]
+    fn buying_ten_buildings_charges_the_series() {
+        let mut state = new_game();
+        state.cookies = n(1e4);
+        let (quantity, price) = state.building_order(1, BuyAmount::Ten).unwrap();
+        assert_eq!(quantity, 10);
+
+        assert!(state.buy_building(1, BuyAmount::Ten));
+        assert_eq!(state.cookies, n(1e4) - price);
+        assert_eq!(state.upgrades[1].count, 10);
+        assert_eq!(state.upgrades[1].cost, n(404.0));
+        assert!((state.cookies_per_second.to_f64() - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bulk_orders_are_all_or_nothing() {
+        let mut state = new_game();
+        state.cookies = n(1_000.0);
+        assert!(!state.buy_building(1, BuyAmount::Hundred));
+        assert!(!state.buy_powerup(0, BuyAmount::Hundred));
+        assert_eq!(state.cookies, n(1_000.0));
+        assert_eq!(state.upgrades[1].count, 0);
+    }
+
+    #[test]
+    fn max_order_spends_down_to_the_next_unit() {
+        let mut state = new_game();
+        state.cookies = n(1e6);
+        assert!(state.buy_powerup(0, BuyAmount::Max));
+        assert!(state.powerups[0].count > 1);
+        assert!(state.cookies < state.powerups[0].cost);
+        assert_eq!(state.cookies_per_click, n(1.0 + state.powerups[0].count as f64));
+
+        state.cookies = BigNum::ZERO;
+        assert_eq!(state.powerup_order(0, BuyAmount::Max).unwrap().0, 1);
+        assert!(!state.buy_powerup(0, BuyAmount::Max));
+    }
+
+    #[test]
+    fn selling_refunds_part_of_the_last_price() {
+        let mut state = new_game();
+        state.cookies = n(1e4);
+        assert!(state.buy_building(1, BuyAmount::Ten));
+        let cookies = state.cookies;
+
+        assert_eq!(state.sell_building(1, SELL_REFUND), Some(n(175.0)));
+        assert_eq!(state.cookies, cookies + n(175.0));
+        assert_eq!(state.upgrades[1].count, 9);
+        assert_eq!(state.upgrades[1].cost, n(351.0));
+        assert!((state.cookies_per_second.to_f64() - 9.0).abs() < 1e-9);
+        assert_eq!(state.total_cookies_earned, BigNum::ZERO);
+    }
+
+    #[test]
+    fn nothing_to_sell() {
+        let mut state = new_game();
+        assert_eq!(state.sell_building(0, SELL_REFUND), None);
+        assert_eq!(state.sell_building(usize::MAX, SELL_REFUND), None);
+        assert!(state.cookies.is_zero());
+    }
+
+    #[test]
+    fn production_starts_at_one_cookie_per_click() {
+        let state = new_game();
+        assert!(state.cookies_per_second.is_zero());
+        assert_eq!(state.cookies_per_click, BigNum::ONE);
+        assert_derived(&state);
+    }
+
+    #[test]
+    fn production_does_not_depend_on_purchase_order() {
+        let mut a = new_game();
+        let mut b = new_game();
+        a.prestige_level = 7;
+        b.prestige_level = 7;
+        a.cookies = n(1e9);
+        b.cookies = n(1e9);
+
+        assert!(a.buy_building(0, BuyAmount::Ten));
+        assert!(a.buy_building(3, BuyAmount::One));
+        assert!(a.buy_powerup(2, BuyAmount::Ten));
+        assert!(a.sell_building(0, SELL_REFUND).is_some());
+
+        assert!(b.buy_powerup(2, BuyAmount::Ten));
+        assert!(b.buy_building(3, BuyAmount::One));
+        assert!(b.buy_building(0, BuyAmount::One));
+        assert!(b.buy_building(0, BuyAmount::One));
+        for _ in 0..7 {
+            assert!(b.buy_building(0, BuyAmount::One));
+        }
+
+        assert_derived(&a);
+        assert_derived(&b);
+        assert_eq!(a.cookies_per_second, b.cookies_per_second);
+        assert_eq!(a.cookies_per_click, b.cookies_per_click);
+    }
+
+    #[test]
+    fn prestige_rederives_production() {
+        let mut state = new_game();
+        state.cookies = state.prestige_cost() * 2.0;
+        state.total_cookies_earned = state.cookies;
+        assert!(state.buy_powerup(4, BuyAmount::Ten));
+        assert!(state.do_prestige());
+        assert_eq!(state.cookies_per_click, BigNum::ONE);
+        assert_derived(&state);
+
+        state.cookies = n(1e6);
+        assert!(state.buy_building(2, BuyAmount::Ten));
+        assert_eq!(state.cookies_per_second, n(80.0) * state.prestige_multiplier());
+        assert_derived(&state);
+    }
+
+    #[test]
+    fn research_waits_for_its_building_count() {
+        let mut state = new_game();
+        state.cookies = n(1e12);
+        let index = state.research_index("double_rendement_grand_mere").unwrap();
+
+        state.upgrades[1].count = 49;
+        assert!(!state.research_available(index));
+        assert!(!state.buy_research(index));
+
+        state.upgrades[1].count = 50;
+        assert!(state.research_available(index));
+        assert!(state.buy_research(index));
+        assert_eq!(state.cookies, n(1e12) - n(50_000.0));
+        assert!(!state.research_available(index));
+        assert!(!state.buy_research(index));
+    }
+
+    #[test]
+    fn multiplier_research_doubles_a_building() {
+        let mut state = new_game();
+        state.cookies = n(1e12);
+        assert!(state.buy_building(1, BuyAmount::Hundred));
+        assert_eq!(state.cookies_per_second, n(100.0));
+
+        for id in ["outils_affutes_grand_mere", "double_rendement_grand_mere"] {
+            assert!(state.buy_research(state.research_index(id).unwrap()));
+        }
+        assert_eq!(state.cookies_per_second, n(400.0));
+        assert_eq!(state.building_cps(1), n(4.0));
+        assert_derived(&state);
+    }
+
+    #[test]
+    fn synergy_scales_with_the_source_building() {
+        let mut state = new_game();
+        state.cookies = n(1e12);
+        state.upgrades[1].count = 10;
+        state.upgrades[2].count = 25;
+        state.refresh_production();
+        let before = state.cookies_per_second;
+
+        assert!(state.buy_research(state.research_index("ble_de_grand_mere").unwrap()));
+        assert_eq!(state.cookies_per_second - before, n(10.0 * 0.5));
+
+        assert!(state.sell_building(2, SELL_REFUND).is_some());
+        assert_eq!(state.building_cps(1), n(1.48));
+        assert_derived(&state);
+    }
+
+    #[test]
+    fn prestige_forgets_research() {
+        let mut state = new_game();
+        state.upgrades[0].count = 10;
+        let index = state.research_index("outils_affutes_curseur").unwrap();
+        state.research[index].bought = true;
+        state.total_cookies_earned = state.prestige_cost();
+
+        assert!(state.do_prestige());
+        assert!(state.research.iter().all(|r| !r.bought));
+    }
+
+    #[test]
+    fn tick_accumulates_fractional_cookies() {
+        let mut state = new_game();
+        state.cookies_per_second = n(0.5);
+
+        assert_eq!(state.tick(1.0), n(0.5));
+        assert_eq!(state.tick(1.0), n(0.5));
+        assert_eq!(state.cookies, n(1.0));
+        assert_eq!(state.total_cookies_earned, n(1.0));
+        assert_eq!(state.lifetime_cookies, n(1.0));
+
+        for _ in 0..100 {
+            state.tick(0.1);
+        }
+        assert!((state.cookies.to_f64() - 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tick_without_production_does_nothing() {
+        let mut state = new_game();
+        assert!(state.tick(1_000.0).is_zero());
+        assert!(state.cookies.is_zero());
+    }
+
+    #[test]
+    fn click_uses_combo_multiplier() {
+        let mut state = new_game();
+        state.cookies_per_click = n(4.0);
+
+        assert_eq!(state.click(0, 0.0).earned, n(4.0));
+        assert_eq!(state.click(2, 0.0).earned, n(12.0));
+        assert_eq!(state.cookies, n(16.0));
+        assert_eq!(state.click_count, 2);
+    }
+
+    #[test]
+    fn critical_clicks_follow_the_roll() {
+        let mut state = new_game();
+        state.powerups[0].effect = PowerUpEffect::CritChance(0.1);
+        state.powerups[0].count = 2;
+        state.refresh_production();
+
+        let normal = state.click(0, 0.5);
+        assert!(!normal.critical);
+        assert_eq!(normal.earned, n(1.0));
+
+        let critical = state.click(0, 0.1);
+        assert!(critical.critical);
+        assert_eq!(critical.earned, n(powerups::BASE_CRIT_MULTIPLIER));
+    }
+
+    #[test]
+    fn prestige_requirement_is_increasing() {
+        let costs: Vec<BigNum> = (0..500).map(prestige_requirement).collect();
+        assert!(costs.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(prestige_requirement(1), n(1e12));
+        assert_eq!(prestige_requirement(3), n(27e12));
+    }
+
+    #[test]
+    fn prestige_gain_follows_run_earnings() {
+        let mut state = new_game();
+        for (earned, gain) in [(0.0, 0), (999e9, 0), (1e12, 1), (7.9e12, 1), (8e12, 2), (27e12, 3), (1e18, 100)] {
+            state.total_cookies_earned = n(earned);
+            assert_eq!(state.prestige_gain(), gain, "{}", earned);
+            assert!(state.prestige_cost() > state.total_cookies_earned);
+        }
+
+        state.total_cookies_earned = n(4.5e12);
+        assert_eq!(state.prestige_progress(), 0.5);
+        assert_eq!(state.prestige_eta(), None);
+        state.cookies_per_second = n(1e9);
+        assert_eq!(state.prestige_eta(), Some(3_500));
+    }
+
+    #[test]
+    fn prestige_needs_run_earnings_not_the_bank() {
+        let mut state = new_game();
+        state.cookies = n(1e15);
+        state.total_cookies_earned = state.prestige_cost() - BigNum::ONE;
+        assert!(!state.can_prestige());
+        assert!(!state.do_prestige());
+        assert_eq!(state.prestige_level, 0);
+
+        // Spending what was baked keeps the prestige available.
+        state.total_cookies_earned = n(8e12);
+        state.cookies = BigNum::ZERO;
+        assert!(state.do_prestige());
+        assert_eq!(state.prestige_level, 2);
+    }
+
+    #[test]
+    fn prestige_resets_the_run_but_keeps_lifetime() {
+        let mut state = new_game();
+        state.prestige_points = n(100.0);
+        state.cookies = n(42.0);
+        state.lifetime_cookies = n(5e15);
+        state.total_cookies_earned = n(1.5e12);
+        state.upgrades[0].count = 12;
+        state.upgrades[0].cost = n(999.0);
+        state.powerups[0].count = 3;
+        state.cookies_per_second = n(77.0);
+        state.click_count = 9;
+
+        assert!(state.do_prestige());
+        assert_eq!(state.prestige_level, 1);
+        assert_eq!(state.prestige_points, n(1_500_100.0));
+        assert!(state.cookies.is_zero());
+        assert!(state.total_cookies_earned.is_zero());
+        assert!(state.cookies_per_second.is_zero());
+        assert_eq!(state.lifetime_cookies, n(5e15));
+        assert_eq!(state.click_count, 9);
+        assert!(state.upgrades.iter().all(|u| u.count == 0 && u.cost == u.base_cost));
+        assert!(state.powerups.iter().all(|p| p.count == 0 && p.cost == p.base_cost));
+        assert_eq!(state.prestige_cost(), prestige_requirement(1));
+    }
+}
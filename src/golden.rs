@@ -0,0 +1,172 @@
+use crate::bignum::BigNum;
+use crate::buffs::{Buff, BuffTarget, BUILDING_BOOST, CLICK_FRENZY, CLOT, FRENZY, GOLDEN_RAIN};
+use crate::resources::GameState;
+
+/// Lucky pays this share of the bank...
+const LUCKY_BANK_SHARE: f64 = 0.15;
+/// ...but no more than this many seconds of production...
+const LUCKY_CPS_SECONDS: f64 = 900.0;
+/// ...plus a few cookies so it is never empty.
+const LUCKY_FLOOR: f64 = 13.0;
+
+/// What clicking a golden cookie does.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GoldenEffect {
+    Frenzy,
+    Lucky,
+    ClickFrenzy,
+    BuildingBoost,
+    GoldenRain,
+    /// The bad one: production halved for a while.
+    Clot,
+}
+
+/// Relative odds of each effect.
+const EFFECT_WEIGHTS: [(GoldenEffect, f32); 6] = [
+    (GoldenEffect::Frenzy, 40.0),
+    (GoldenEffect::Lucky, 35.0),
+    (GoldenEffect::ClickFrenzy, 9.0),
+    (GoldenEffect::BuildingBoost, 6.0),
+    (GoldenEffect::GoldenRain, 5.0),
+    (GoldenEffect::Clot, 5.0),
+];
+
+impl GoldenEffect {
+    /// Effect for a uniform `roll` in [0, 1), plus where the roll landed inside
+    /// that effect's band, rescaled to [0, 1). The second value is as uniform
+    /// as `roll`, so one random value picks both the effect and, say, the
+    /// boosted building.
+    pub fn roll(roll: f32) -> (Self, f32) {
+        let total: f32 = EFFECT_WEIGHTS.iter().map(|(_, w)| w).sum();
+        let mut target = roll.clamp(0.0, 1.0) * total;
+        for (effect, weight) in EFFECT_WEIGHTS {
+            if target < weight {
+                return (effect, target / weight);
+            }
+            target -= weight;
+        }
+        (EFFECT_WEIGHTS[EFFECT_WEIGHTS.len() - 1].0, 1.0)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum GoldenOutcome {
+    Cookies(BigNum),
+    Buff(Buff),
+}
+
+impl GameState {
+    /// How fast the golden cookie timer runs: golden rain, heavenly upgrades and
+    /// challenge rewards.
+    pub fn golden_spawn_rate(&self) -> f64 {
+        self.buffs.multiplier(&BuffTarget::GoldenSpawnRate) * self.golden_frequency() * self.challenge_golden_frequency()
+    }
+
+    /// Instant payout of Lucky: a share of the bank, capped by production.
+    pub fn lucky_payout(&self) -> BigNum {
+        let bank = self.cookies * LUCKY_BANK_SHARE;
+        let cap = self.cookies_per_second * LUCKY_CPS_SECONDS;
+        (if bank < cap { bank } else { cap }).floor() + BigNum::from(LUCKY_FLOOR)
+    }
+
+    /// Applies `effect`. `pick`, in [0, 1), chooses the building of a boost among
+    /// those owned; with none owned it pays out like Lucky instead.
+    pub fn apply_golden_effect(&mut self, effect: GoldenEffect, pick: f32) -> GoldenOutcome {
+        self.golden_cookies_clicked += 1;
+        self.run.golden_cookies += 1;
+
+        let owned: Vec<String> = self.upgrades.iter()
+            .filter(|u| u.count > 0)
+            .map(|u| u.id.clone())
+            .collect();
+        let buff = match effect {
+            GoldenEffect::Frenzy => FRENZY.grant(BuffTarget::Production),
+            GoldenEffect::ClickFrenzy => CLICK_FRENZY.grant(BuffTarget::Click),
+            GoldenEffect::GoldenRain => GOLDEN_RAIN.grant(BuffTarget::GoldenSpawnRate),
+            GoldenEffect::Clot => CLOT.grant(BuffTarget::Production),
+            GoldenEffect::BuildingBoost if !owned.is_empty() => {
+                let index = ((pick.clamp(0.0, 1.0) * owned.len() as f32) as usize).min(owned.len() - 1);
+                BUILDING_BOOST.grant(BuffTarget::Building(owned[index].clone()))
+            }
+            GoldenEffect::Lucky | GoldenEffect::BuildingBoost => {
+                let cookies = self.lucky_payout();
+                self.earn(cookies);
+                return GoldenOutcome::Cookies(cookies);
+            }
+        };
+
+        self.add_buff(buff.clone());
+        GoldenOutcome::Buff(buff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn n(value: f64) -> BigNum {
+        BigNum::from(value)
+    }
+
+    #[test]
+    fn rolls_follow_the_weights() {
+        assert_eq!(GoldenEffect::roll(0.0).0, GoldenEffect::Frenzy);
+        assert_eq!(GoldenEffect::roll(0.399).0, GoldenEffect::Frenzy);
+        assert_eq!(GoldenEffect::roll(0.4).0, GoldenEffect::Lucky);
+        assert_eq!(GoldenEffect::roll(0.8).0, GoldenEffect::ClickFrenzy);
+        assert_eq!(GoldenEffect::roll(0.87).0, GoldenEffect::BuildingBoost);
+        assert_eq!(GoldenEffect::roll(0.92).0, GoldenEffect::GoldenRain);
+        assert_eq!(GoldenEffect::roll(0.97).0, GoldenEffect::Clot);
+        assert_eq!(GoldenEffect::roll(1.0).0, GoldenEffect::Clot);
+    }
+
+    #[test]
+    fn picks_spread_across_the_effect_band() {
+        let (effect, pick) = GoldenEffect::roll(0.84);
+        assert_eq!(effect, GoldenEffect::BuildingBoost);
+        assert!(pick < 0.01);
+
+        let (effect, pick) = GoldenEffect::roll(0.87);
+        assert_eq!(effect, GoldenEffect::BuildingBoost);
+        assert!((pick - 0.5).abs() < 0.01);
+
+        let (effect, pick) = GoldenEffect::roll(0.8999);
+        assert_eq!(effect, GoldenEffect::BuildingBoost);
+        assert!(pick > 0.99);
+    }
+
+    #[test]
+    fn lucky_is_capped_by_bank_and_production() {
+        let mut state = GameState::default();
+        assert_eq!(state.lucky_payout(), n(13.0));
+
+        state.cookies = n(1_000_000.0);
+        state.cookies_per_second = n(10.0);
+        assert_eq!(state.lucky_payout(), n(9_013.0));
+
+        state.cookies_per_second = n(1_000.0);
+        assert_eq!(state.lucky_payout(), n(150_013.0));
+    }
+
+    #[test]
+    fn effects_grant_cookies_or_buffs() {
+        let mut state = GameState::default();
+        assert_eq!(state.apply_golden_effect(GoldenEffect::Lucky, 0.0), GoldenOutcome::Cookies(n(13.0)));
+        assert_eq!(state.cookies, n(13.0));
+
+        // Nothing to boost yet: falls back to Lucky.
+        assert!(matches!(state.apply_golden_effect(GoldenEffect::BuildingBoost, 0.5), GoldenOutcome::Cookies(_)));
+
+        state.upgrades[2].count = 1;
+        let GoldenOutcome::Buff(buff) = state.apply_golden_effect(GoldenEffect::BuildingBoost, 0.99) else {
+            panic!("un bâtiment possédé devait être boosté");
+        };
+        assert_eq!(buff.target, BuffTarget::Building(state.upgrades[2].id.clone()));
+
+        state.apply_golden_effect(GoldenEffect::Frenzy, 0.0);
+        state.apply_golden_effect(GoldenEffect::GoldenRain, 0.0);
+        assert_eq!(state.buffs.len(), 3);
+        assert_eq!(state.buffs.multiplier(&BuffTarget::GoldenSpawnRate), 4.0);
+        assert_eq!(state.golden_cookies_clicked, 5);
+    }
+}
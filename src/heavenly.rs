@@ -0,0 +1,258 @@
+use serde::Deserialize;
+use crate::bignum::BigNum;
+use crate::locale::tr_with;
+use crate::resources::GameState;
+use crate::utils::{format_decimal, format_number};
+
+/// What a heavenly upgrade does, for every run to come.
+#[derive(Clone, Copy, Deserialize, PartialEq, Debug)]
+pub enum HeavenlyEffect {
+    /// Runs start with this many cookies in the bank.
+    StartingCookies(f64),
+    /// Added to the `offline_efficiency` of the catalog rules.
+    OfflineEfficiency(f64),
+    /// Golden cookies show up this many times as often.
+    GoldenFrequency(f64),
+    /// This many of each building survive a prestige.
+    KeepBuildings(u128),
+}
+
+/// Permanent upgrade bought with heavenly chips, once all of `parents` are.
+#[derive(Clone, Debug)]
+pub struct HeavenlyUpgrade {
+    pub id: String,
+    pub name: String,
+    pub emoji: String,
+    pub description: String,
+    pub cost: BigNum,
+    pub parents: Vec<String>,
+    pub effect: HeavenlyEffect,
+    pub bought: bool,
+}
+
+/// Offline efficiency never goes past full production.
+const MAX_OFFLINE_EFFICIENCY: f64 = 1.0;
+
+impl HeavenlyEffect {
+    /// Description of an upgrade, in the current language and notation.
+    pub fn describe(self) -> String {
+        match self {
+            HeavenlyEffect::StartingCookies(cookies) => {
+                tr_with("heavenly.effect.starting_cookies", &[("count", format_number(cookies))])
+            }
+            HeavenlyEffect::OfflineEfficiency(share) => {
+                tr_with("heavenly.effect.offline", &[("percent", format_decimal(share * 100.0))])
+            }
+            HeavenlyEffect::GoldenFrequency(factor) => {
+                tr_with("heavenly.effect.golden", &[("multiplier", format_decimal(factor))])
+            }
+            HeavenlyEffect::KeepBuildings(count) => {
+                tr_with("heavenly.effect.keep_buildings", &[("count", count.to_string())])
+            }
+        }
+    }
+
+    /// Amounts are finite and non-negative, a share stays a share, and golden
+    /// cookies keep showing up.
+    pub fn is_valid(self) -> bool {
+        match self {
+            HeavenlyEffect::StartingCookies(cookies) => cookies.is_finite() && cookies >= 0.0,
+            HeavenlyEffect::OfflineEfficiency(share) => (0.0..=1.0).contains(&share),
+            HeavenlyEffect::GoldenFrequency(factor) => factor.is_finite() && factor > 0.0,
+            HeavenlyEffect::KeepBuildings(_) => true,
+        }
+    }
+}
+
+/// The heavenly tree, from `assets/data/heavenly.ron` and the content packs.
+pub fn get_heavenly() -> Vec<HeavenlyUpgrade> {
+    crate::catalog::current().heavenly()
+}
+
+/// Ids grouped by depth in the tree: roots first, then their children, and so on.
+pub fn heavenly_tiers(upgrades: &[HeavenlyUpgrade]) -> Vec<Vec<String>> {
+    fn depth(upgrades: &[HeavenlyUpgrade], upgrade: &HeavenlyUpgrade) -> usize {
+        upgrade.parents.iter()
+            .filter_map(|p| upgrades.iter().find(|u| &u.id == p))
+            .map(|parent| depth(upgrades, parent) + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    let mut tiers: Vec<Vec<String>> = Vec::new();
+    for upgrade in upgrades {
+        let depth = depth(upgrades, upgrade);
+        if tiers.len() <= depth {
+            tiers.resize(depth + 1, Vec::new());
+        }
+        tiers[depth].push(upgrade.id.clone());
+    }
+    tiers
+}
+
+impl GameState {
+    pub fn heavenly_index(&self, id: &str) -> Option<usize> {
+        self.heavenly.iter().position(|h| h.id == id)
+    }
+
+    /// Chips left to spend: every prestige point earned so far minus what the
+    /// heavenly upgrades bought cost.
+    pub fn heavenly_chips(&self) -> BigNum {
+        let spent = self.heavenly.iter()
+            .filter(|h| h.bought)
+            .fold(BigNum::ZERO, |total, h| total + h.cost);
+        if spent >= self.prestige_points {
+            BigNum::ZERO
+        } else {
+            self.prestige_points - spent
+        }
+    }
+
+    /// Not bought yet and every parent is.
+    pub fn heavenly_available(&self, index: usize) -> bool {
+        self.heavenly.get(index).is_some_and(|upgrade| {
+            !upgrade.bought && upgrade.parents.iter()
+                .all(|p| self.heavenly.iter().any(|h| &h.id == p && h.bought))
+        })
+    }
+
+    pub fn buy_heavenly(&mut self, index: usize) -> bool {
+        if !self.heavenly_available(index) || self.heavenly_chips() < self.heavenly[index].cost {
+            return false;
+        }
+
+        self.heavenly[index].bought = true;
+        true
+    }
+
+    fn heavenly_effects(&self) -> impl Iterator<Item = HeavenlyEffect> + '_ {
+        self.heavenly.iter().filter(|h| h.bought).map(|h| h.effect)
+    }
+
+    /// Bank a new run starts with.
+    pub fn starting_cookies(&self) -> BigNum {
+        let cookies = self.heavenly_effects()
+            .filter_map(|e| match e {
+                HeavenlyEffect::StartingCookies(cookies) => Some(cookies),
+                _ => None,
+            })
+            .fold(0.0, f64::max);
+        BigNum::from(cookies)
+    }
+
+    /// Share of the production credited while the game is closed.
+    pub fn offline_efficiency(&self) -> f64 {
+        let bonus: f64 = self.heavenly_effects()
+            .filter_map(|e| match e {
+                HeavenlyEffect::OfflineEfficiency(share) => Some(share),
+                _ => None,
+            })
+            .sum();
+        (crate::catalog::current().rules.offline_efficiency + bonus).min(MAX_OFFLINE_EFFICIENCY)
+    }
+
+    /// How much faster than normal the golden cookie timer runs.
+    pub fn golden_frequency(&self) -> f64 {
+        self.heavenly_effects()
+            .filter_map(|e| match e {
+                HeavenlyEffect::GoldenFrequency(factor) => Some(factor),
+                _ => None,
+            })
+            .product()
+    }
+
+    /// Units of each building a prestige leaves in place.
+    pub fn kept_buildings(&self) -> u128 {
+        self.heavenly_effects()
+            .filter_map(|e| match e {
+                HeavenlyEffect::KeepBuildings(count) => Some(count),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn n(value: f64) -> BigNum {
+        BigNum::from(value)
+    }
+
+    fn state_with_chips(chips: f64) -> GameState {
+        GameState { prestige_points: n(chips), ..GameState::default() }
+    }
+
+    #[test]
+    fn tree_is_well_formed() {
+        let upgrades = get_heavenly();
+        for upgrade in &upgrades {
+            for parent in &upgrade.parents {
+                assert!(upgrades.iter().any(|u| &u.id == parent), "{} -> {}", upgrade.id, parent);
+            }
+        }
+
+        let tiers = heavenly_tiers(&upgrades);
+        assert_eq!(tiers[0], vec!["benediction".to_string()]);
+        assert_eq!(tiers.iter().map(Vec::len).sum::<usize>(), upgrades.len());
+        assert!(tiers[2].contains(&"grand_heritage".to_string()));
+    }
+
+    #[test]
+    fn buying_needs_the_parents_and_spends_chips() {
+        let mut state = state_with_chips(1e9);
+        let root = state.heavenly_index("benediction").unwrap();
+        let child = state.heavenly_index("sommeil_paisible").unwrap();
+
+        assert!(!state.buy_heavenly(child));
+        assert!(state.buy_heavenly(root));
+        assert!(!state.buy_heavenly(root));
+        assert_eq!(state.heavenly_chips(), n(1e9 - 1e6));
+
+        assert!(state.buy_heavenly(child));
+        assert_eq!(state.heavenly_chips(), n(1e9 - 1e6 - 1e8));
+
+        let costly = state.heavenly_index("reves_lucides").unwrap();
+        assert!(!state.buy_heavenly(costly));
+    }
+
+    #[test]
+    fn effects_add_up() {
+        let mut state = state_with_chips(1e13);
+        assert_eq!(state.starting_cookies(), BigNum::ZERO);
+        assert_eq!(state.offline_efficiency(), 0.5);
+        assert_eq!(state.golden_frequency(), 1.0);
+        assert_eq!(state.kept_buildings(), 0);
+
+        for upgrade in &mut state.heavenly {
+            upgrade.bought = true;
+        }
+        assert_eq!(state.starting_cookies(), n(1e12));
+        assert_eq!(state.offline_efficiency(), 1.0);
+        assert_eq!(state.golden_frequency(), 1.875);
+        assert_eq!(state.kept_buildings(), 50);
+    }
+
+    #[test]
+    fn heavenly_upgrades_survive_prestige() {
+        let mut state = state_with_chips(1e12);
+        for id in ["benediction", "heritage"] {
+            let index = state.heavenly_index(id).unwrap();
+            assert!(state.buy_heavenly(index));
+        }
+        state.upgrades[0].count = 25;
+        state.upgrades[1].count = 4;
+        state.total_cookies_earned = state.prestige_cost();
+
+        assert!(state.do_prestige());
+        assert_eq!(state.heavenly.iter().filter(|h| h.bought).count(), 2);
+        assert_eq!(state.cookies, n(1e6));
+        assert_eq!(state.upgrades[0].count, 10);
+        assert_eq!(state.upgrades[1].count, 4);
+        assert_eq!(state.upgrades[0].cost, state.upgrades[0].calculate_cost());
+        assert_eq!(state.upgrades[2].cost, state.upgrades[2].base_cost);
+        assert!(state.cookies_per_second > BigNum::ZERO);
+    }
+}
@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Language {
+    #[default]
+    Fr,
+    En,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::Fr, Language::En];
+
+    pub fn next(self) -> Language {
+        let index = Language::ALL.iter().position(|l| *l == self).unwrap_or(0);
+        Language::ALL[(index + 1) % Language::ALL.len()]
+    }
+
+    /// Plural category of `count`: French treats 0 and 1 as singular, English only 1.
+    fn plural(self, count: f64) -> &'static str {
+        let one = match self {
+            Language::Fr => count < 2.0,
+            Language::En => count == 1.0,
+        };
+        if one { "one" } else { "other" }
+    }
+
+    fn table_source(self) -> &'static str {
+        match self {
+            Language::Fr => include_str!("../assets/lang/fr.ron"),
+            Language::En => include_str!("../assets/lang/en.ron"),
+        }
+    }
+}
+
+/// Language every string is rendered in, set from `Settings`.
+static CURRENT: RwLock<Language> = RwLock::new(Language::Fr);
+static TABLES: OnceLock<HashMap<Language, HashMap<String, String>>> = OnceLock::new();
+
+pub fn language() -> Language {
+    *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn set_language(language: Language) {
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = language;
+}
+
+fn parse_table(text: &str) -> Result<HashMap<String, String>, String> {
+    ron::from_str(text).map_err(|e| e.to_string())
+}
+
+fn tables() -> &'static HashMap<Language, HashMap<String, String>> {
+    TABLES.get_or_init(|| {
+        Language::ALL.iter()
+            .map(|&l| (l, parse_table(l.table_source()).expect("table de traduction invalide")))
+            .collect()
+    })
+}
+
+/// Looks `key` up in `language`, then in French, which is always complete.
+fn lookup(language: Language, key: &str) -> Option<&'static str> {
+    let tables = tables();
+    tables[&language].get(key)
+        .or_else(|| tables[&Language::Fr].get(key))
+        .map(String::as_str)
+}
+
+fn fill(template: &str, args: &[(&str, String)]) -> String {
+    args.iter().fold(template.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+/// Translated string. A missing key shows up as the key itself.
+pub fn tr(key: &str) -> String {
+    lookup(language(), key).unwrap_or(key).to_string()
+}
+
+/// Translated string with `{name}` placeholders replaced.
+pub fn tr_with(key: &str, args: &[(&str, String)]) -> String {
+    fill(&tr(key), args)
+}
+
+/// Picks `key.one` or `key.other` for `count`, then fills the placeholders.
+pub fn tr_count(key: &str, count: f64, args: &[(&str, String)]) -> String {
+    tr_with(&format!("{}.{}", key, language().plural(count)), args)
+}
+
+/// Translation of a catalog entry, or `fallback` (the catalog's own text) when the
+/// table has none. Content packs can ship without translations this way.
+pub fn tr_or(key: &str, fallback: &str) -> String {
+    lookup(language(), key).map_or_else(|| fallback.to_string(), str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_parse_and_match() {
+        let french = parse_table(Language::Fr.table_source()).unwrap();
+        let english = parse_table(Language::En.table_source()).unwrap();
+
+        // English may translate catalog entries French leaves to the data files,
+        // but every UI string must exist in both.
+        for key in french.keys() {
+            assert!(english.contains_key(key), "manquant en anglais : {}", key);
+        }
+        for key in english.keys().filter(|k| !is_content_key(k)) {
+            assert!(french.contains_key(key), "manquant en français : {}", key);
+        }
+    }
+
+    #[test]
+    fn placeholders_match_between_languages() {
+        let french = parse_table(Language::Fr.table_source()).unwrap();
+        let english = parse_table(Language::En.table_source()).unwrap();
+        for (key, text) in &french {
+            assert_eq!(placeholders(text), placeholders(&english[key]), "{}", key);
+        }
+    }
+
+    #[test]
+    fn english_translates_the_whole_catalog() {
+        let english = parse_table(Language::En.table_source()).unwrap();
+        let catalog = crate::catalog::Catalog::embedded();
+        let keys = catalog.buildings.iter().map(|b| format!("building.{}.name", b.id))
+            .chain(catalog.powerups.iter().map(|p| format!("powerup.{}.name", p.id)))
+            .chain(catalog.achievements.iter().map(|a| format!("achievement.{}.name", a.id)));
+        for key in keys {
+            assert!(english.contains_key(&key), "{}", key);
+        }
+    }
+
+    #[test]
+    fn plural_rules() {
+        assert_eq!(Language::Fr.plural(0.0), "one");
+        assert_eq!(Language::Fr.plural(1.0), "one");
+        assert_eq!(Language::Fr.plural(1.5), "one");
+        assert_eq!(Language::Fr.plural(2.0), "other");
+        assert_eq!(Language::En.plural(0.0), "other");
+        assert_eq!(Language::En.plural(1.0), "one");
+        assert_eq!(Language::En.plural(2.0), "other");
+    }
+
+    #[test]
+    fn fill_replaces_every_placeholder() {
+        let args = [("count", "3".to_string()), ("name", "Mine".to_string())];
+        assert_eq!(fill("{count} x {name}, {count}", &args), "3 x Mine, 3");
+        assert_eq!(fill("{unknown}", &args), "{unknown}");
+    }
+
+    #[test]
+    fn lookup_uses_the_language_table() {
+        assert_eq!(lookup(Language::En, "ui.cancel"), Some("Cancel"));
+        assert_eq!(lookup(Language::En, "n.existe.pas"), None);
+        assert_eq!(lookup(Language::Fr, "building.curseur.name"), None);
+        assert_eq!(lookup(Language::En, "building.curseur.name"), Some("Cursor"));
+    }
+
+    /// `building.<id>.name` and the like: French takes them from the catalog.
+    fn is_content_key(key: &str) -> bool {
+        let parts: Vec<&str> = key.split('.').collect();
+        parts.len() == 3 && ["building", "powerup", "achievement"].contains(&parts[0])
+    }
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut found: Vec<&str> = text.split('{').skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+            .collect();
+        found.sort();
+        found
+    }
+}
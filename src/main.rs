@@ -1,5 +1,5 @@
+
 use bevy::prelude::*;
-use cosmic_text::FontSystem;
 
 mod components;
 mod resources;
@@ -11,31 +11,73 @@ mod ui_icons;
 mod powerups;
 mod upgrades;
 mod achievements;
+mod audio;
+mod bignum;
+mod buffs;
+mod catalog;
+mod challenges;
+mod economy;
+mod golden;
+mod heavenly;
+mod locale;
+mod research;
+mod runs;
+mod save;
+mod settings;
+mod sim;
 
 use system::*;
-use crate::resources::{load_or_create_game_state, ClickPower, ComboSystem, GoldenCookieTimer, SaveTimer};
+use crate::resources::{apply_offline_progress, BuyAmount, ComboSystem, GoldenCookieTimer, SaveTimer};
+use crate::save::{load_or_create_game_state, SaveLocation};
+use crate::utils::now_secs;
 use crate::ui::{achievement_popup_system, mouse_scroll};
+use crate::catalog::{ContentPack, ContentPackLoader};
+use crate::settings::Settings;
+use crate::audio::{PlaySound, Synth};
+use bevy::audio::AddAudioSource;
 
 const BACKGROUND_COLOR: Color = Color::srgb(0.05, 0.05, 0.08);
 
 fn main() {
-    let save = load_or_create_game_state();
-    let mut font = FontSystem::new();
-    App::new()
+    catalog::install_from_disk();
+
+    if std::env::args().any(|arg| arg == "--headless") {
+        sim::run_headless();
+        return;
+    }
+
+    let location = SaveLocation::from_args(std::env::args());
+    println!("💾 Dossier de sauvegarde : {} (slot {})", location.dir.display(), location.slot);
+    let settings = Settings::load_or_migrate(&location.dir, &location.path());
+    settings.apply();
+    let (mut save, recovery) = load_or_create_game_state(&location);
+    let offline = apply_offline_progress(&mut save, now_secs());
+    let mut app = App::new();
+    app
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 title: "🍪 COOKIE EMPIRE DELUXE 🍪".into(),
-                resolution: (1200.0, 800.0).into(),
+                resolution: (1200.0_f32, 800.0_f32).into(),
                 resizable: true,
                 ..default()
             }),
             ..default()
+        }).set(AssetPlugin {
+            // Hot reload of assets/data/, only built in with the `hot_reload` feature.
+            watch_for_changes_override: Some(cfg!(feature = "hot_reload")),
+            ..default()
         }))
+        .init_asset::<ContentPack>()
+        .register_asset_loader(ContentPackLoader)
+        .add_audio_source::<Synth>()
+        .add_event::<PlaySound>()
         .insert_resource(ClearColor(BACKGROUND_COLOR))
         .insert_resource(save.clone())
-        .insert_resource(SaveTimer(Timer::from_seconds(5.0, TimerMode::Repeating)))
-        .insert_resource(ClickPower(save.cookies_per_click))
+        .insert_resource(location)
+        .insert_resource(SaveTimer(Timer::from_seconds(settings.autosave_seconds as f32, TimerMode::Repeating)))
+        .insert_resource(settings)
         .insert_resource(GoldenCookieTimer::default())
+        .insert_resource(BuyAmount::default())
         .insert_resource(ComboSystem {
             clicks: 0,
             combo: 0,
@@ -43,8 +85,8 @@ fn main() {
             active: false,
         })
         //.insert_resource(AchievementList::new())
-        .add_systems(PreStartup, ui_fonts::load_fonts)
-        .add_systems(Startup, ui::setup_ui)
+        .add_systems(PreStartup, (ui_fonts::load_fonts, catalog::load_catalog_assets))
+        .add_systems(Startup, (ui::setup_ui, audio::setup_audio))
         .add_systems(
             Update,
             (
@@ -71,6 +113,50 @@ fn main() {
                 update_stats_system,
             ),
         )
-        .add_systems(Update, achievement_popup_system)
-        .run();
+        .add_systems(
+            Update,
+            (
+                achievement_popup_system,
+                save_slot_button_system,
+                offline_progress_popup_system,
+                save_recovery_popup_system,
+                save_transfer_button_system,
+                import_file_drop_system,
+                confirm_dialog_system,
+                buy_amount_button_system,
+                research_button_system,
+                update_research_ui_system,
+                catalog_reload_system,
+                settings_menu_system,
+                setting_button_system,
+                apply_settings_system,
+                localized_text_system,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                volume_slider_system,
+                buff_tick_system,
+                buff_bar_system,
+                audio::mute_hotkey_system,
+                audio::play_sound_system,
+                audio::sound_cleanup_system,
+                audio::music_volume_system,
+                heavenly_menu_system,
+                heavenly_node_button_system,
+                update_heavenly_ui_system,
+                run_summary_system,
+                challenge_choice_system,
+                challenge_system,
+            ),
+        );
+
+    if let Some(offline) = offline {
+        app.insert_resource(offline);
+    }
+    if let Some(recovery) = recovery {
+        app.insert_resource(recovery);
+    }
+    app.run();
 }
@@ -1,86 +1,180 @@
 use serde::{Deserialize, Serialize};
+use crate::bignum::BigNum;
+use crate::locale::tr_with;
+use crate::resources::GameState;
+use crate::utils::format_decimal;
+
+/// Each power-up costs this much more than the previous one.
+pub const COST_GROWTH: f64 = 1.2;
+
+/// Critical clicks are worth this many normal ones before any power-up.
+pub const BASE_CRIT_MULTIPLIER: f64 = 3.0;
+/// Crit chance never goes past this, however many power-ups are owned.
+pub const MAX_CRIT_CHANCE: f64 = 0.5;
+
+/// What each unit of a power-up does to a click.
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Debug, Default)]
+pub enum PowerUpEffect {
+    /// Adds `multiplier` cookies per click.
+    #[default]
+    Flat,
+    /// Adds this share of the production to every click.
+    CpsShare(f64),
+    /// Adds this chance for a click to be critical.
+    CritChance(f64),
+    /// Adds this much to the multiplier of critical clicks.
+    CritMultiplier(f64),
+    /// Adds this bonus per combo level to every click of a streak.
+    Streak(f64),
+}
+
+impl PowerUpEffect {
+    /// Default description of a power-up, for a unit worth `multiplier` cookies.
+    pub fn describe(self, multiplier: BigNum) -> String {
+        let percent = |key: &str, share: f64| tr_with(key, &[("percent", format_decimal(share * 100.0))]);
+        match self {
+            PowerUpEffect::Flat => tr_with("powerup.default_description", &[("multiplier", format_decimal(multiplier))]),
+            PowerUpEffect::CpsShare(share) => percent("powerup.effect.cps_share", share),
+            PowerUpEffect::CritChance(chance) => percent("powerup.effect.crit_chance", chance),
+            PowerUpEffect::CritMultiplier(bonus) => {
+                tr_with("powerup.effect.crit_multiplier", &[("multiplier", format_decimal(bonus))])
+            }
+            PowerUpEffect::Streak(bonus) => percent("powerup.effect.streak", bonus),
+        }
+    }
+
+    /// Every value is a finite, non-negative amount and a chance stays a chance.
+    pub fn is_valid(self) -> bool {
+        match self {
+            PowerUpEffect::Flat => true,
+            PowerUpEffect::CritChance(chance) => (0.0..=1.0).contains(&chance),
+            PowerUpEffect::CpsShare(value)
+            | PowerUpEffect::CritMultiplier(value)
+            | PowerUpEffect::Streak(value) => value.is_finite() && value >= 0.0,
+        }
+    }
+}
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct PowerUp {
+    pub id: String,
     pub name: String,
     pub emoji: String,
-    pub base_cost: u128,
-    pub cost: u128,
-    pub multiplier: u128,
+    pub base_cost: BigNum,
+    pub cost: BigNum,
+    pub multiplier: BigNum,
+    #[serde(default)]
+    pub effect: PowerUpEffect,
     pub count: u128,
     pub description: String,
 }
 
 impl PowerUp {
-    pub fn calculate_cost(&self) -> u128 {
-        (self.base_cost as f64 * 1.2_f64.powi(self.count as i32)) as u128
+    pub fn calculate_cost(&self) -> BigNum {
+        (self.base_cost * BigNum::from(COST_GROWTH).powf(self.count as f64)).floor()
     }
 }
 
+/// Power-ups in shop order, from `assets/data/powerups.ron` and the content packs.
+pub fn get_powerups() -> Vec<PowerUp> {
+    crate::catalog::current().powerups()
+}
+
+impl GameState {
+    /// Sum over the power-ups owned of what `value` picks out of their effect, per unit.
+    fn powerup_total(&self, value: impl Fn(PowerUpEffect) -> Option<f64>) -> f64 {
+        self.powerups.iter()
+            .filter_map(|p| value(p.effect).map(|v| v * p.count as f64))
+            .sum()
+    }
+
+    /// Share of the production every click adds.
+    pub fn cps_share(&self) -> f64 {
+        self.powerup_total(|e| match e {
+            PowerUpEffect::CpsShare(share) => Some(share),
+            _ => None,
+        })
+    }
+
+    pub fn crit_chance(&self) -> f64 {
+        self.powerup_total(|e| match e {
+            PowerUpEffect::CritChance(chance) => Some(chance),
+            _ => None,
+        })
+        .min(MAX_CRIT_CHANCE)
+    }
+
+    pub fn crit_multiplier(&self) -> f64 {
+        BASE_CRIT_MULTIPLIER + self.powerup_total(|e| match e {
+            PowerUpEffect::CritMultiplier(bonus) => Some(bonus),
+            _ => None,
+        })
+    }
 
-fn pu(name: &str, base: u128, mult: u128) -> PowerUp {
-    PowerUp {
-        name: name.into(),
-        emoji: "".into(),
-        base_cost: base,
-        cost: base,
-        multiplier: mult,
-        count: 0,
-        description: format!("+{} cookies par clic", mult),
+    /// Click multiplier of a streak at combo level `combo`.
+    pub fn streak_multiplier(&self, combo: u128) -> f64 {
+        let bonus = self.powerup_total(|e| match e {
+            PowerUpEffect::Streak(bonus) => Some(bonus),
+            _ => None,
+        });
+        1.0 + bonus * combo as f64
+    }
+
+    /// What a click outside any combo is worth on average, crits included.
+    pub fn expected_click(&self) -> BigNum {
+        self.cookies_per_click * (1.0 + self.crit_chance() * (self.crit_multiplier() - 1.0))
     }
 }
 
-pub fn get_powerups() -> Vec<PowerUp> {
-    vec![
-        pu("Clic renforcé", 100, 1),
-        pu("Double clic", 500, 2),
-        pu("Triple frappe", 2_000, 5),
-        pu("Clic furieux", 10_000, 10),
-        pu("Doigt d'acier", 50_000, 25),
-        pu("Bras cybernétique", 250_000, 50),
-        pu("Main divine", 1_000_000, 100),
-        pu("Frappe amplifiée", 2_500_000, 150),
-        pu("Clic cosmique", 5_000_000, 250),
-        pu("Poing stellaire", 12_000_000, 375),
-        pu("Doigt quantique", 20_000_000, 500),
-        pu("Coup atomique", 50_000_000, 750),
-        pu("Main fractale", 100_000_000, 1_000),
-        pu("Frappe moléculaire", 250_000_000, 1_500),
-        pu("Hyper clic", 500_000_000, 2_500),
-        pu("Clic critique", 1_000_000_000, 5_000),
-        pu("Frappe explosive", 1_750_000_000, 7_500),
-        pu("Clic instable", 2_500_000_000, 10_000),
-        pu("Poing du titan", 3_750_000_000, 15_000),
-        pu("Résonance du combo", 5_000_000_000, 20_000),
-        pu("Frappe sismique", 7_500_000_000, 35_000),
-        pu("Clic abyssal", 10_000_000_000, 50_000),
-        pu("Main des profondeurs", 25_000_000_000, 75_000),
-        pu("Clic divin absolu", 50_000_000_000, 100_000),
-        pu("Frappe céleste", 75_000_000_000, 175_000),
-        pu("Main temporelle", 100_000_000_000, 250_000),
-        pu("Clic distordu", 250_000_000_000, 375_000),
-        pu("Doigt infini", 500_000_000_000, 500_000),
-        pu("Frappe éternelle", 750_000_000_000, 750_000),
-        pu("Clic paradoxal", 1_000_000_000_000, 1_000_000),
-        pu("Poing galactique", 2_500_000_000_000, 1_500_000),
-        pu("Clic dimensionnel", 5_000_000_000_000, 2_500_000),
-        pu("Frappe du vide", 7_500_000_000_000, 3_750_000),
-        pu("Clic cosmologique", 10_000_000_000_000, 5_000_000),
-        pu("Main de l'oubli", 25_000_000_000_000, 7_500_000),
-        pu("Clic primordial", 50_000_000_000_000, 10_000_000),
-        pu("Frappe ancestrale", 75_000_000_000_000, 17_500_000),
-        pu("Clic universel", 100_000_000_000_000, 25_000_000),
-        pu("Poing de la création", 250_000_000_000_000, 37_500_000),
-        pu("Clic omnipotent", 500_000_000_000_000, 50_000_000),
-        pu("Frappe du destin", 750_000_000_000_000, 75_000_000),
-        pu("Clic absolu", 1_000_000_000_000_000, 100_000_000),
-        pu("Main de l'alpha", 2_500_000_000_000_000, 175_000_000),
-        pu("Frappe éthérée", 5_000_000_000_000_000, 250_000_000),
-        pu("Clic transcendant", 10_000_000_000_000_000, 400_000_000),
-        pu("Poing originel", 17_500_000_000_000_000, 650_000_000),
-        pu("Main du vide", 25_000_000_000_000_000, 1_000_000_000),
-        pu("Frappe omnisciente", 50_000_000_000_000_000, 1_750_000_000),
-        pu("Doigt du destin", 100_000_000_000_000_000, 3_000_000_000),
-        pu("Clic du néant", 200_000_000_000_000_000, 5_000_000_000),
-    ]
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn n(value: f64) -> BigNum {
+        BigNum::from(value)
+    }
+
+    fn state_with(effect: PowerUpEffect, count: u128) -> GameState {
+        let mut state = GameState::default();
+        state.powerups[0].effect = effect;
+        state.powerups[0].count = count;
+        state.refresh_production();
+        state
+    }
+
+    #[test]
+    fn cps_share_adds_production_to_clicks() {
+        let mut state = state_with(PowerUpEffect::CpsShare(0.01), 5);
+        assert_eq!(state.cookies_per_click, n(1.0));
+
+        state.upgrades[1].count = 100;
+        state.refresh_production();
+        assert_eq!(state.cookies_per_click, BigNum::ONE + state.cookies_per_second * 0.05);
+    }
+
+    #[test]
+    fn crit_chance_is_capped() {
+        let state = state_with(PowerUpEffect::CritChance(0.02), 10);
+        assert!((state.crit_chance() - 0.2).abs() < 1e-9);
+        assert_eq!(state.crit_multiplier(), BASE_CRIT_MULTIPLIER);
+        assert_eq!(state.expected_click(), n(1.4));
+
+        let state = state_with(PowerUpEffect::CritChance(0.02), 1_000);
+        assert_eq!(state.crit_chance(), MAX_CRIT_CHANCE);
+    }
+
+    #[test]
+    fn streak_grows_with_the_combo() {
+        let state = state_with(PowerUpEffect::Streak(0.05), 4);
+        assert_eq!(state.streak_multiplier(0), 1.0);
+        assert_eq!(state.streak_multiplier(5), 2.0);
+    }
+
+    #[test]
+    fn effects_are_validated() {
+        assert!(PowerUpEffect::CritChance(0.5).is_valid());
+        assert!(!PowerUpEffect::CritChance(1.5).is_valid());
+        assert!(!PowerUpEffect::CpsShare(-0.1).is_valid());
+        assert!(!PowerUpEffect::Streak(f64::NAN).is_valid());
+    }
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use crate::bignum::BigNum;
+use crate::locale::{tr, tr_with};
+use crate::upgrades::{get_upgrade, Upgrade};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ResearchEffect {
+    /// Multiplies the output of a building.
+    Multiplier(String, f64),
+    /// Each unit of the first building adds this fraction to the second one's output.
+    Synergy(String, String, f64),
+}
+
+/// One-time upgrade, shown once `requirement` (building id, count) is met.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Research {
+    pub id: String,
+    pub name: String,
+    pub emoji: String,
+    pub description: String,
+    pub cost: BigNum,
+    pub requirement: (String, u128),
+    pub effect: ResearchEffect,
+    pub bought: bool,
+}
+
+/// (building count needed, cost in units of the building's base cost, id prefix)
+const MULTIPLIER_TIERS: [(u128, f64, &str); 3] = [
+    (10, 10.0, "outils_affutes"),
+    (50, 500.0, "double_rendement"),
+    (100, 50_000.0, "maitrise_totale"),
+];
+
+fn rs(id: &str, cost: BigNum, requirement: (&str, u128), effect: ResearchEffect) -> Research {
+    Research {
+        id: id.into(),
+        name: String::new(),
+        emoji: "".into(),
+        description: String::new(),
+        cost,
+        requirement: (requirement.0.into(), requirement.1),
+        effect,
+        bought: false,
+    }
+}
+
+/// Synergies are described with the buildings' names in the current language.
+fn describe_synergy(research: &mut Research, buildings: &[Upgrade]) {
+    let name = |id: &str| buildings.iter().find(|b| b.id == id).map_or_else(String::new, |b| b.name.clone());
+    if let ResearchEffect::Synergy(source, target, bonus) = &research.effect {
+        research.name = tr(&format!("research.{}.name", research.id));
+        research.description = tr_with("research.synergy.description", &[
+            ("percent", (bonus * 100.0).to_string()),
+            ("target", name(target)),
+            ("source", name(source)),
+        ]);
+    }
+}
+
+pub fn get_research() -> Vec<Research> {
+    let buildings = get_upgrade();
+    let mut research = Vec::new();
+
+    for building in &buildings {
+        let args = [("building", building.name.clone())];
+        for (count, cost, tier) in MULTIPLIER_TIERS {
+            let mut multiplier = rs(
+                &format!("{}_{}", tier, building.id),
+                (building.base_cost * cost).floor(),
+                (&building.id, count),
+                ResearchEffect::Multiplier(building.id.clone(), 2.0),
+            );
+            multiplier.name = tr_with(&format!("research.tier.{}", tier), &args);
+            multiplier.description = tr_with("research.multiplier.description", &args);
+            research.push(multiplier);
+        }
+    }
+
+    let synergy = |source: &str, target: &str, bonus| ResearchEffect::Synergy(source.into(), target.into(), bonus);
+    research.extend([
+        rs("ble_de_grand_mere", 5e5.into(), ("ferme", 25), synergy("ferme", "grand_mere", 0.02)),
+        rs("mamies_mineuses", 3e6.into(), ("mine", 25), synergy("grand_mere", "mine", 0.01)),
+        rs("minerai_sucre", 6e7.into(), ("usine", 25), synergy("mine", "usine", 0.01)),
+        rs("prets_de_mamie", 7e8.into(), ("banque", 25), synergy("grand_mere", "banque", 0.005)),
+        rs("rituels_enchantes", 1e11.into(), ("tour_de_magie", 25), synergy("temple", "tour_de_magie", 0.01)),
+        rs("doigts_interdimensionnels", 2.5e12.into(), ("portail", 25), synergy("curseur", "portail", 0.001)),
+        rs("paradoxe_fructueux", 4e13.into(), ("machine_temporelle", 25), synergy("portail", "machine_temporelle", 0.01)),
+    ]);
+
+    // Content packs may drop buildings the synergies refer to.
+    let exists = |id: &String| buildings.iter().any(|b| &b.id == id);
+    research.retain(|r| exists(&r.requirement.0) && match &r.effect {
+        ResearchEffect::Multiplier(target, _) => exists(target),
+        ResearchEffect::Synergy(source, target, _) => exists(source) && exists(target),
+    });
+    for r in &mut research {
+        describe_synergy(r, &buildings);
+    }
+    research
+}
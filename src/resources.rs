@@ -1,51 +1,72 @@
 use bevy::prelude::*;
-use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
+use crate::bignum::BigNum;
+use crate::buffs::ActiveBuffs;
 use crate::achievements::{get_achievements, AchievementList};
+use crate::heavenly::{get_heavenly, HeavenlyUpgrade};
 use crate::powerups::{get_powerups, PowerUp};
-use crate::system::PRESTIGE_BUFF;
+use crate::research::{get_research, Research};
+use crate::runs::{RunStats, RunSummary};
 use crate::upgrades::{get_upgrade, Upgrade};
-use crate::utils::random_spawn_time;
+use crate::utils::{format_number, random_spawn_time};
 
-const SAVE_FILE: &str = "cookie_save.json";
-
-#[derive(Resource, Serialize, Deserialize, Clone)]
+#[derive(Resource, Clone)]
 pub struct GameState {
-    pub cookies: u128,
-    pub total_cookies_earned: u128,
-    pub cookies_per_second: f64,
-    pub cookies_per_click: u128,
+    pub cookies: BigNum,
+    pub total_cookies_earned: BigNum,
+    pub cookies_per_second: BigNum,
+    pub cookies_per_click: BigNum,
     pub upgrades: Vec<Upgrade>,
     pub powerups: Vec<PowerUp>,
+    pub research: Vec<Research>,
     pub prestige_level: u128,
-    pub prestige_points: u128,
-    pub lifetime_cookies: u128,
+    /// Heavenly chips earned over every prestige, spent or not.
+    pub prestige_points: BigNum,
+    /// Permanent upgrades bought with the chips; a prestige leaves them alone.
+    pub heavenly: Vec<HeavenlyUpgrade>,
+    pub lifetime_cookies: BigNum,
     pub click_count: u128,
     pub golden_cookies_clicked: u128,
     pub achievements: AchievementList,
     pub combo: u128,
-    pub cps_buffer: f64,
+    pub last_saved: u64,
+    /// Timed multipliers running right now.
+    pub buffs: ActiveBuffs,
+    pub run: RunStats,
+    /// Every finished run, oldest first.
+    pub run_history: Vec<RunSummary>,
+    /// Id of the challenge this run is played under, if any.
+    pub challenge: Option<String>,
+    /// Challenges whose goal was met at least once; their rewards are permanent.
+    pub challenges_completed: Vec<String>,
 }
 
 impl Default for GameState {
     fn default() -> Self {
-        Self{
-            cookies: 0,
-            total_cookies_earned: 0,
-            cookies_per_second: 0.0,
-            cookies_per_click: 0,
+        let mut state = Self {
+            cookies: BigNum::ZERO,
+            total_cookies_earned: BigNum::ZERO,
+            cookies_per_second: BigNum::ZERO,
+            cookies_per_click: BigNum::ZERO,
             upgrades: get_upgrade(),
             powerups: get_powerups(),
+            research: get_research(),
             prestige_level: 0,
-            prestige_points: 0,
-            lifetime_cookies: 0,
+            prestige_points: BigNum::ZERO,
+            heavenly: get_heavenly(),
+            lifetime_cookies: BigNum::ZERO,
             click_count: 0,
             golden_cookies_clicked: 0,
             achievements: get_achievements(),
             combo: 0,
-            cps_buffer: 0.0,
-        }
+            last_saved: 0,
+            buffs: ActiveBuffs::default(),
+            run: RunStats::default(),
+            run_history: Vec::new(),
+            challenge: None,
+            challenges_completed: Vec::new(),
+        };
+        state.refresh_production();
+        state
     }
 }
 
@@ -53,9 +74,6 @@ impl Default for GameState {
 #[derive(Resource)]
 pub struct SaveTimer(pub Timer);
 
-#[derive(Resource)]
-pub struct ClickPower(pub u128);
-
 #[derive(Resource)]
 pub struct GoldenCookieTimer(pub Timer);
 
@@ -65,6 +83,43 @@ impl Default for GoldenCookieTimer {
     }
 }
 
+/// How many units the building and power-up buttons buy per press.
+#[derive(Resource, Clone, Copy, PartialEq, Debug, Default)]
+pub enum BuyAmount {
+    #[default]
+    One,
+    Ten,
+    Hundred,
+    /// As many as the bank allows.
+    Max,
+}
+
+impl BuyAmount {
+    pub const ALL: [BuyAmount; 4] = [BuyAmount::One, BuyAmount::Ten, BuyAmount::Hundred, BuyAmount::Max];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BuyAmount::One => "x1",
+            BuyAmount::Ten => "x10",
+            BuyAmount::Hundred => "x100",
+            BuyAmount::Max => "Max",
+        }
+    }
+}
+
+/// Set while the prestige confirmation dialog is open, with the challenge picked
+/// for the next run.
+#[derive(Resource, Default)]
+pub struct PendingPrestige {
+    pub challenge: Option<String>,
+}
+
+#[derive(Resource, Clone, Copy)]
+pub struct OfflineProgress {
+    pub seconds: u64,
+    pub cookies: BigNum,
+}
+
 #[derive(Resource)]
 pub struct ComboSystem {
     pub clicks: u128,
@@ -73,89 +128,24 @@ pub struct ComboSystem {
     pub timer: Timer,
 }
 
-pub fn load_or_create_game_state() -> GameState {
-    let save_path = PathBuf::from(SAVE_FILE);
-
-    if save_path.exists() {
-        if let Ok(data) = fs::read_to_string(&save_path) {
-            if let Ok(saved_state) = serde_json::from_str::<GameState>(&data) {
-                let mut fresh_state = GameState::default();
-
-                fresh_state.cookies = saved_state.cookies;
-                fresh_state.prestige_level = saved_state.prestige_level;
-                fresh_state.total_cookies_earned = saved_state.total_cookies_earned;
-                fresh_state.click_count = saved_state.click_count;
-                fresh_state.golden_cookies_clicked = saved_state.golden_cookies_clicked;
-                fresh_state.achievements = saved_state.achievements;
-                fresh_state.cookies_per_second = 1.0;
-                fresh_state.cookies_per_click = 1;
-
-
-                for saved_upgrade in &saved_state.upgrades {
-                    if let Some(fresh_upgrade) = fresh_state.upgrades.iter_mut()
-                        .find(|u| u.name == saved_upgrade.name) {
-                        fresh_upgrade.count = saved_upgrade.count;
-                        fresh_upgrade.cost = fresh_upgrade.calculate_cost();
-                        let multiplier = 1.0 + (fresh_state.prestige_level as f64 * PRESTIGE_BUFF);
-                        fresh_state.cookies_per_second += fresh_upgrade.cps * fresh_upgrade.count as f64 * multiplier;
-                    }
-                }
-
-                for saved_powerup in &saved_state.powerups {
-                    if let Some(fresh_powerup) = fresh_state.powerups.iter_mut()
-                        .find(|p| p.name == saved_powerup.name) {
-                        fresh_powerup.count = saved_powerup.count;
-                        fresh_powerup.cost = fresh_powerup.calculate_cost();
-                        fresh_state.cookies_per_click += fresh_powerup.multiplier * fresh_powerup.count;
-                    }
-                }
-
-
-                println!("💾 Sauvegarde chargée et mise à jour : {} cookies, {} CPS, {} CPC",
-                         fresh_state.cookies, fresh_state.cookies_per_second, fresh_state.cookies_per_click);
-                println!("✨ {} nouveaux upgrades disponibles !",
-                         fresh_state.upgrades.len() - saved_state.upgrades.len());
-                println!("✨ {} nouveaux powerups disponibles !",
-                         fresh_state.powerups.len() - saved_state.powerups.len());
-
-                return fresh_state;
-            }
-        }
+/// Credits the production missed since `last_saved`, at the offline efficiency
+/// (the catalog rules plus heavenly bonuses) and capped to `offline_max_hours`.
+pub fn apply_offline_progress(game_state: &mut GameState, now: u64) -> Option<OfflineProgress> {
+    if game_state.last_saved == 0 || now <= game_state.last_saved {
+        return None;
     }
 
-    let powerups = get_powerups();
-    let upgrades = get_upgrade();
-    let achievements = get_achievements();
-
-    println!("✨ Nouvelle partie créée: {} powerups load {} builds load", powerups.len(), upgrades.len());
-    GameState {
-        cookies: 0,
-        total_cookies_earned: 0,
-        cookies_per_second: 0.0,
-        cookies_per_click: 1,
-        click_count: 0,
-        golden_cookies_clicked: 0,
-        prestige_level: 0,
-        prestige_points: 0,
-        lifetime_cookies: 0,
-        upgrades,
-        powerups,
-        achievements,
-        combo: 0,
-        cps_buffer: 0.0,
+    let seconds = (now - game_state.last_saved).min(crate::catalog::current().rules.offline_max_seconds());
+    // Buffs keep running while the game is closed, so they don't boost the catch-up.
+    game_state.tick_buffs((now - game_state.last_saved) as f64);
+    let cookies = (game_state.cookies_per_second * (seconds as f64 * game_state.offline_efficiency())).floor();
+    if cookies < BigNum::ONE {
+        return None;
     }
-}
-
-
 
+    game_state.earn(cookies);
+    game_state.last_saved = now;
 
-
-
-
-pub fn save_game_state(game_state: &GameState) {
-    if let Ok(data) = serde_json::to_string_pretty(game_state) {
-        if let Err(e) = fs::write(SAVE_FILE, data) {
-            eprintln!("❌ Erreur de sauvegarde : {}", e);
-        }
-    }
-}
\ No newline at end of file
+    println!("🌙 Hors ligne pendant {}s : +{} cookies", seconds, format_number(cookies));
+    Some(OfflineProgress { seconds, cookies })
+}
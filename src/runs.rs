@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+use crate::bignum::BigNum;
+use crate::economy::PRESTIGE_BUFF;
+use crate::resources::GameState;
+
+/// Oldest runs are dropped past this many, to keep saves small.
+pub const RUN_HISTORY_MAX: usize = 100;
+
+/// Counters of the run in progress, started over by each prestige.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(default)]
+pub struct RunStats {
+    /// Unix time the run began, 0 when unknown (saves older than run tracking).
+    pub started: u64,
+    pub clicks: u128,
+    pub golden_cookies: u128,
+    pub best_combo: u128,
+}
+
+/// A finished run, as shown on the summary screen and kept in the history.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+#[serde(default)]
+pub struct RunSummary {
+    /// Prestige level the run ended on.
+    pub level: u128,
+    pub started: u64,
+    pub ended: u64,
+    pub cookies: BigNum,
+    pub clicks: u128,
+    pub golden_cookies: u128,
+    pub best_combo: u128,
+    /// Heavenly chips the prestige granted.
+    pub chips: BigNum,
+}
+
+impl RunSummary {
+    /// Seconds the run lasted, if its start is known.
+    pub fn duration(&self) -> Option<u64> {
+        (self.started > 0).then(|| self.ended.saturating_sub(self.started))
+    }
+}
+
+/// What prestiging right now would change, for the confirmation dialog.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PrestigePreview {
+    pub next_level: u128,
+    pub multiplier: f64,
+    pub next_multiplier: f64,
+    pub chips: BigNum,
+}
+
+impl GameState {
+    pub fn prestige_preview(&self) -> PrestigePreview {
+        let next_level = self.prestige_level + self.prestige_gain();
+        PrestigePreview {
+            next_level,
+            multiplier: self.prestige_multiplier(),
+            next_multiplier: 1.0 + next_level as f64 * PRESTIGE_BUFF,
+            chips: self.prestige_chips(),
+        }
+    }
+
+    /// Prestiges and files the run that just ended into the history, returning it.
+    /// `None` when prestige is not affordable.
+    pub fn ascend(&mut self, now: u64) -> Option<RunSummary> {
+        let run = self.run;
+        let cookies = self.total_cookies_earned;
+        let points = self.prestige_points;
+        if !self.do_prestige() {
+            return None;
+        }
+
+        let summary = RunSummary {
+            level: self.prestige_level,
+            started: run.started,
+            ended: now,
+            cookies,
+            clicks: run.clicks,
+            golden_cookies: run.golden_cookies,
+            best_combo: run.best_combo,
+            chips: if self.prestige_points > points { self.prestige_points - points } else { BigNum::ZERO },
+        };
+        self.run = RunStats { started: now, ..RunStats::default() };
+        self.run_history.push(summary.clone());
+        if self.run_history.len() > RUN_HISTORY_MAX {
+            let excess = self.run_history.len() - RUN_HISTORY_MAX;
+            self.run_history.drain(..excess);
+        }
+        Some(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn n(value: f64) -> BigNum {
+        BigNum::from(value)
+    }
+
+    #[test]
+    fn preview_matches_what_prestige_does() {
+        let mut state = GameState { prestige_level: 2, prestige_points: n(1_000.0), ..GameState::default() };
+        state.total_cookies_earned = n(8e12);
+
+        let preview = state.prestige_preview();
+        assert_eq!(preview.next_level, 4);
+        assert_eq!(preview.chips, n(8e6));
+
+        assert!(state.do_prestige());
+        assert_eq!(state.prestige_level, preview.next_level);
+        assert_eq!(state.prestige_points, n(8e6 + 1_000.0));
+        assert_eq!(state.prestige_multiplier(), preview.next_multiplier);
+    }
+
+    #[test]
+    fn ascend_records_the_run_and_starts_a_new_one() {
+        let mut state = GameState::default();
+        assert_eq!(state.ascend(500), None);
+        assert!(state.run_history.is_empty());
+
+        state.run = RunStats { started: 100, clicks: 40, golden_cookies: 2, best_combo: 7 };
+        state.total_cookies_earned = n(3e12);
+
+        let summary = state.ascend(1_000).unwrap();
+        assert_eq!(summary.level, 1);
+        assert_eq!(summary.duration(), Some(900));
+        assert_eq!(summary.clicks, 40);
+        assert_eq!(summary.best_combo, 7);
+        assert_eq!(summary.chips, n(3e6));
+        assert_eq!(state.run_history, vec![summary]);
+        assert_eq!(state.run, RunStats { started: 1_000, ..RunStats::default() });
+    }
+
+    #[test]
+    fn history_keeps_the_latest_runs() {
+        let mut state = GameState::default();
+        for level in 0..RUN_HISTORY_MAX as u128 + 5 {
+            state.total_cookies_earned = state.prestige_cost();
+            state.ascend(level as u64 + 1).unwrap();
+        }
+        assert_eq!(state.run_history.len(), RUN_HISTORY_MAX);
+        assert_eq!(state.run_history[0].level, 6);
+        assert_eq!(state.run_history.last().unwrap().level, RUN_HISTORY_MAX as u128 + 5);
+    }
+
+    #[test]
+    fn unknown_start_has_no_duration() {
+        let summary = RunSummary { ended: 50, ..RunSummary::default() };
+        assert_eq!(summary.duration(), None);
+    }
+}
@@ -0,0 +1,823 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use bevy::prelude::Resource;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use crate::achievements::get_achievements;
+use crate::bignum::BigNum;
+use crate::heavenly::get_heavenly;
+use crate::buffs::{buff_def, ActiveBuffs, BuffTarget};
+use crate::powerups::get_powerups;
+use crate::research::get_research;
+use crate::runs::{RunStats, RunSummary};
+use crate::resources::GameState;
+use crate::upgrades::get_upgrade;
+use crate::utils::now_secs;
+
+/// Where saves lived before slots existed: the current working directory.
+const LEGACY_SAVE_FILE: &str = "cookie_save.json";
+const LAST_SLOT_FILE: &str = "last_slot";
+
+/// Overrides the save directory, same as `--save-dir`.
+pub const SAVE_DIR_ENV: &str = "COOKIE_SAVE_DIR";
+pub const SAVE_SLOTS: usize = 3;
+
+/// Number of previous saves kept next to each slot file as `slot_<n>.json.1..N`, newest first.
+pub const SAVE_BACKUPS: usize = 3;
+
+/// Prefix of exported save strings, bumped if the envelope itself ever changes.
+const EXPORT_PREFIX: &str = "CKE1.";
+
+/// Largest decompressed save accepted on import, far above any real save.
+const MAX_IMPORT_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Version written by `save_game_state`. Bump it together with a new entry in `MIGRATIONS`.
+pub const SAVE_VERSION: u32 = 4;
+
+type Migration = fn(Value) -> Result<Value, String>;
+
+/// `MIGRATIONS[i]` turns a version `i + 1` save into a version `i + 2` save.
+const MIGRATIONS: &[Migration] = &[
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+];
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct SavedCount {
+    pub id: String,
+    pub count: u128,
+}
+
+/// A running buff: the rest of it comes from its definition.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SavedBuff {
+    pub id: String,
+    pub target: BuffTarget,
+    pub duration: f64,
+    pub remaining: f64,
+}
+
+/// What actually goes to disk: progress only, the catalogs always come from the game itself.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+#[serde(default)]
+pub struct SaveData {
+    pub save_version: u32,
+    pub last_saved: u64,
+    pub cookies: BigNum,
+    pub total_cookies_earned: BigNum,
+    pub lifetime_cookies: BigNum,
+    pub prestige_level: u128,
+    pub prestige_points: BigNum,
+    pub click_count: u128,
+    pub golden_cookies_clicked: u128,
+    pub combo: u128,
+    pub buildings: Vec<SavedCount>,
+    pub powerups: Vec<SavedCount>,
+    /// Ids of the achievements unlocked.
+    pub achievements: Vec<String>,
+    /// Ids of the research bought this run.
+    pub research: Vec<String>,
+    /// Ids of the heavenly upgrades bought, kept across prestiges.
+    pub heavenly: Vec<String>,
+    pub buffs: Vec<SavedBuff>,
+    pub run: RunStats,
+    pub run_history: Vec<RunSummary>,
+    /// Id of the challenge being played, if any.
+    pub challenge: Option<String>,
+    pub challenges_completed: Vec<String>,
+}
+
+/// Directory holding the slots and the slot currently played.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct SaveLocation {
+    pub dir: PathBuf,
+    pub slot: usize,
+}
+
+impl SaveLocation {
+    /// `--save-dir <dir>` wins over `COOKIE_SAVE_DIR`, which wins over the platform data
+    /// directory (`$XDG_DATA_HOME/cookie_clicker` on Linux). `--slot <n>` picks the slot,
+    /// otherwise the last one played is reused.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Self {
+        let mut dir = std::env::var_os(SAVE_DIR_ENV).map(PathBuf::from);
+        let mut slot = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--save-dir" => dir = args.next().map(PathBuf::from).or(dir),
+                "--slot" => slot = args.next().and_then(|s| s.parse().ok()),
+                _ => {}
+            }
+        }
+
+        let dir = dir.unwrap_or_else(|| {
+            dirs::data_dir()
+                .map(|d| d.join("cookie_clicker"))
+                .unwrap_or_else(|| PathBuf::from("."))
+        });
+        let slot = slot
+            .or_else(|| fs::read_to_string(dir.join(LAST_SLOT_FILE)).ok()?.trim().parse().ok())
+            .unwrap_or(1)
+            .clamp(1, SAVE_SLOTS);
+
+        Self { dir, slot }
+    }
+
+    pub fn slot_path(&self, slot: usize) -> PathBuf {
+        self.dir.join(format!("slot_{}.json", slot))
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.slot_path(self.slot)
+    }
+
+    pub fn remember_slot(&self) {
+        if let Err(e) = fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(self.dir.join(LAST_SLOT_FILE), self.slot.to_string()))
+        {
+            eprintln!("❌ Impossible de mémoriser le slot : {}", e);
+        }
+    }
+
+    /// Moves a pre-slot `cookie_save.json` from the working directory into slot 1.
+    fn adopt_legacy_save(&self) {
+        let legacy = PathBuf::from(LEGACY_SAVE_FILE);
+        let target = self.slot_path(1);
+        if !legacy.exists() || target.exists() {
+            return;
+        }
+
+        match fs::create_dir_all(&self.dir).and_then(|_| fs::copy(&legacy, &target)) {
+            Ok(_) => println!("💾 Ancienne sauvegarde copiée vers {}", target.display()),
+            Err(e) => eprintln!("❌ Impossible de copier l'ancienne sauvegarde : {}", e),
+        }
+    }
+}
+
+/// A validated import waiting for the player to confirm it.
+#[derive(Resource, Clone)]
+pub struct PendingImport(pub SaveData);
+
+/// Set when the main save could not be read and an older backup was used instead.
+#[derive(Resource, Clone)]
+pub struct SaveRecovery {
+    pub file: String,
+}
+
+impl SaveData {
+    pub fn from_game_state(game_state: &GameState) -> Self {
+        let list = &game_state.achievements;
+        Self {
+            save_version: SAVE_VERSION,
+            last_saved: game_state.last_saved,
+            cookies: game_state.cookies,
+            total_cookies_earned: game_state.total_cookies_earned,
+            lifetime_cookies: game_state.lifetime_cookies,
+            prestige_level: game_state.prestige_level,
+            prestige_points: game_state.prestige_points,
+            click_count: game_state.click_count,
+            golden_cookies_clicked: game_state.golden_cookies_clicked,
+            combo: game_state.combo,
+            buildings: game_state.upgrades.iter()
+                .map(|u| SavedCount { id: u.id.clone(), count: u.count })
+                .collect(),
+            powerups: game_state.powerups.iter()
+                .map(|p| SavedCount { id: p.id.clone(), count: p.count })
+                .collect(),
+            achievements: list.achievements.iter().zip(&list.unlocked)
+                .filter(|(_, unlocked)| **unlocked)
+                .map(|(a, _)| a.id.clone())
+                .collect(),
+            research: game_state.research.iter()
+                .filter(|r| r.bought)
+                .map(|r| r.id.clone())
+                .collect(),
+            heavenly: game_state.heavenly.iter()
+                .filter(|h| h.bought)
+                .map(|h| h.id.clone())
+                .collect(),
+            buffs: game_state.buffs.iter()
+                .map(|b| SavedBuff {
+                    id: b.def.id.to_string(),
+                    target: b.target.clone(),
+                    duration: b.duration,
+                    remaining: b.remaining,
+                })
+                .collect(),
+            run: game_state.run,
+            run_history: game_state.run_history.clone(),
+            challenge: game_state.challenge.clone(),
+            challenges_completed: game_state.challenges_completed.clone(),
+        }
+    }
+
+    /// Rebuilds a full `GameState` from the current catalogs. Entries that no longer
+    /// exist are ignored, new ones start at zero.
+    pub fn into_game_state(self) -> GameState {
+        let mut state = GameState {
+            cookies: self.cookies,
+            total_cookies_earned: self.total_cookies_earned,
+            lifetime_cookies: self.lifetime_cookies,
+            prestige_level: self.prestige_level,
+            prestige_points: self.prestige_points,
+            click_count: self.click_count,
+            golden_cookies_clicked: self.golden_cookies_clicked,
+            combo: self.combo,
+            last_saved: self.last_saved,
+            cookies_per_second: BigNum::ZERO,
+            cookies_per_click: BigNum::ZERO,
+            upgrades: get_upgrade(),
+            powerups: get_powerups(),
+            research: get_research(),
+            heavenly: get_heavenly(),
+            achievements: get_achievements(),
+            buffs: ActiveBuffs::default(),
+            run: self.run,
+            run_history: self.run_history,
+            challenge: self.challenge,
+            challenges_completed: self.challenges_completed,
+        };
+
+        for saved in &self.buildings {
+            if let Some(upgrade) = state.upgrades.iter_mut().find(|u| u.id == saved.id) {
+                upgrade.count = saved.count;
+                upgrade.cost = upgrade.calculate_cost();
+            }
+        }
+
+        for saved in &self.powerups {
+            if let Some(powerup) = state.powerups.iter_mut().find(|p| p.id == saved.id) {
+                powerup.count = saved.count;
+                powerup.cost = powerup.calculate_cost();
+            }
+        }
+
+        for research in &mut state.research {
+            research.bought = self.research.contains(&research.id);
+        }
+        for heavenly in &mut state.heavenly {
+            heavenly.bought = self.heavenly.contains(&heavenly.id);
+        }
+
+        for saved in &self.buffs {
+            if let Some(def) = buff_def(&saved.id) {
+                let mut buff = def.grant(saved.target.clone());
+                buff.duration = saved.duration;
+                buff.remaining = saved.remaining;
+                state.buffs.add(buff);
+            }
+        }
+        state.refresh_production();
+
+        let list = &mut state.achievements;
+        for (achievement, unlocked) in list.achievements.iter().zip(list.unlocked.iter_mut()) {
+            *unlocked = self.achievements.contains(&achievement.id);
+        }
+
+        state
+    }
+}
+
+/// Parses a save of any known version and runs it through `MIGRATIONS` up to `SAVE_VERSION`.
+pub fn parse_save(data: &str) -> Result<SaveData, String> {
+    let mut value: Value = serde_json::from_str(data).map_err(|e| e.to_string())?;
+
+    // Saves written before checksums existed are trusted as-is.
+    if let Some(stored) = value.as_object_mut().and_then(|o| o.remove("checksum"))
+        && stored.as_str() != Some(checksum(&value).as_str())
+    {
+        return Err("checksum invalide".into());
+    }
+
+    // Saves written before versioning have no `save_version` field.
+    let mut version = value.get("save_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(1) as u32;
+
+    if version == 0 || version > SAVE_VERSION {
+        return Err(format!("version de sauvegarde inconnue : {}", version));
+    }
+
+    while version < SAVE_VERSION {
+        value = MIGRATIONS[version as usize - 1](value)?;
+        version += 1;
+        value["save_version"] = json!(version);
+    }
+
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// v1 serialized the whole `GameState`, catalogs included, with achievements
+/// unlocked by position.
+fn migrate_v1_to_v2(old: Value) -> Result<Value, String> {
+    let counts = |key: &str| -> Vec<Value> {
+        old[key].as_array()
+            .map(|items| items.iter()
+                .map(|item| json!({ "name": item["name"], "count": item["count"] }))
+                .collect())
+            .unwrap_or_default()
+    };
+
+    let achievements: Vec<Value> = match (
+        old["achievements"]["achievements"].as_array(),
+        old["achievements"]["unlocked"].as_array(),
+    ) {
+        (Some(list), Some(unlocked)) => list.iter().zip(unlocked)
+            .filter(|(_, unlocked)| unlocked.as_bool() == Some(true))
+            .map(|(achievement, _)| achievement["name"].clone())
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut new = json!({
+        "buildings": counts("upgrades"),
+        "powerups": counts("powerups"),
+        "achievements": achievements,
+    });
+
+    for key in [
+        "last_saved", "cookies", "total_cookies_earned", "lifetime_cookies", "prestige_level",
+        "prestige_points", "click_count", "golden_cookies_clicked", "combo",
+    ] {
+        if let Some(v) = old.get(key) {
+            new[key] = v.clone();
+        }
+    }
+
+    Ok(new)
+}
+
+/// v3 stores cookie amounts as `BigNum` strings instead of JSON integers.
+fn migrate_v2_to_v3(mut save: Value) -> Result<Value, String> {
+    for key in ["cookies", "total_cookies_earned", "lifetime_cookies", "prestige_points"] {
+        if let Some(Value::Number(n)) = save.get(key) {
+            save[key] = Value::String(n.to_string());
+        }
+    }
+    Ok(save)
+}
+
+/// v4 refers to buildings, power-ups, achievements and research by id instead of
+/// display name, so names can be translated or fixed without breaking saves.
+fn migrate_v3_to_v4(mut save: Value) -> Result<Value, String> {
+    for key in ["buildings", "powerups"] {
+        if let Some(items) = save.get_mut(key).and_then(Value::as_array_mut) {
+            for item in items {
+                let id = item.get("name").and_then(Value::as_str).map(legacy_id).unwrap_or_default();
+                item["id"] = json!(id);
+                if let Some(item) = item.as_object_mut() {
+                    item.remove("name");
+                }
+            }
+        }
+    }
+
+    for key in ["achievements", "research"] {
+        if let Some(items) = save.get_mut(key).and_then(Value::as_array_mut) {
+            for item in items {
+                if let Some(name) = item.as_str() {
+                    *item = json!(legacy_id(name));
+                }
+            }
+        }
+    }
+    Ok(save)
+}
+
+/// The id a v3 name maps to: lowercase ASCII, accents dropped, anything else
+/// collapsed into `_`. Frozen: the catalogs' ids were generated with it, so it
+/// must not change even if new content uses other ids.
+fn legacy_id(name: &str) -> String {
+    let mut id = String::new();
+    for c in name.to_lowercase().chars() {
+        let replacement = match c {
+            'à' | 'â' | 'ä' => "a",
+            'ç' => "c",
+            'é' | 'è' | 'ê' | 'ë' => "e",
+            'î' | 'ï' => "i",
+            'ô' | 'ö' => "o",
+            'ù' | 'û' | 'ü' => "u",
+            'ÿ' => "y",
+            'œ' => "oe",
+            'æ' => "ae",
+            c if c.is_ascii_alphanumeric() => {
+                id.push(c);
+                continue;
+            }
+            _ => "_",
+        };
+        if replacement == "_" && (id.is_empty() || id.ends_with('_')) {
+            continue;
+        }
+        id.push_str(replacement);
+    }
+    id.trim_end_matches('_').to_string()
+}
+
+/// Serializes a save with its checksum embedded.
+pub fn encode_save(save: &SaveData) -> Result<String, String> {
+    serde_json::to_string_pretty(&checked_value(save)?).map_err(|e| e.to_string())
+}
+
+fn checked_value(save: &SaveData) -> Result<Value, String> {
+    let mut value = serde_json::to_value(save).map_err(|e| e.to_string())?;
+    value["checksum"] = json!(checksum(&value));
+    Ok(value)
+}
+
+/// Compact text form of a save: checksummed JSON, zlib-compressed, base64 encoded.
+pub fn export_save(save: &SaveData) -> Result<String, String> {
+    let json = serde_json::to_string(&checked_value(save)?).map_err(|e| e.to_string())?;
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(json.as_bytes()).map_err(|e| e.to_string())?;
+    let compressed = encoder.finish().map_err(|e| e.to_string())?;
+
+    Ok(format!("{}{}", EXPORT_PREFIX, URL_SAFE_NO_PAD.encode(compressed)))
+}
+
+/// Reverses `export_save`, then validates and migrates like a save file.
+pub fn import_save(text: &str) -> Result<SaveData, String> {
+    let payload: String = text.trim()
+        .strip_prefix(EXPORT_PREFIX)
+        .ok_or("ce texte n'est pas une sauvegarde exportée")?
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+
+    let compressed = URL_SAFE_NO_PAD.decode(payload).map_err(|e| e.to_string())?;
+    let mut json = String::new();
+    ZlibDecoder::new(compressed.as_slice())
+        .take(MAX_IMPORT_BYTES)
+        .read_to_string(&mut json)
+        .map_err(|e| e.to_string())?;
+    if json.len() as u64 >= MAX_IMPORT_BYTES {
+        return Err("sauvegarde trop volumineuse".into());
+    }
+
+    parse_save(&json)
+}
+
+fn checksum(value: &Value) -> String {
+    let canonical = serde_json::to_string(value).unwrap_or_default();
+    format!("{:08x}", crc32fast::hash(canonical.as_bytes()))
+}
+
+fn backup_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Writes `data` to `path` with a `.tmp` suffix and flushes it to disk, ready
+/// to be renamed over `path`. Returns the temporary path.
+pub fn write_temp_file(path: &Path, data: &str) -> Result<PathBuf, String> {
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp = PathBuf::from(tmp_name);
+
+    let mut file = fs::File::create(&tmp).map_err(|e| e.to_string())?;
+    file.write_all(data.as_bytes()).map_err(|e| e.to_string())?;
+    file.sync_all().map_err(|e| e.to_string())?;
+    Ok(tmp)
+}
+
+/// Writes to a temporary file first, then shifts the backups and renames it
+/// into place, so a crash never leaves a half-written save behind.
+pub fn write_save(path: &Path, save: &SaveData) -> Result<(), String> {
+    let tmp = write_temp_file(path, &encode_save(save)?)?;
+
+    for n in (1..SAVE_BACKUPS).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            fs::rename(&from, backup_path(path, n + 1)).map_err(|e| e.to_string())?;
+        }
+    }
+    if SAVE_BACKUPS > 0 && path.exists() {
+        fs::rename(path, backup_path(path, 1)).map_err(|e| e.to_string())?;
+    }
+
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// Reads the save, falling back to the newest valid backup. Returns the file actually used.
+pub fn read_save(path: &Path) -> Result<(SaveData, PathBuf), Vec<String>> {
+    let mut errors = Vec::new();
+
+    let candidates = std::iter::once(path.to_path_buf())
+        .chain((1..=SAVE_BACKUPS).map(|n| backup_path(path, n)));
+
+    for candidate in candidates {
+        if !candidate.exists() {
+            continue;
+        }
+        match fs::read_to_string(&candidate).map_err(|e| e.to_string()).and_then(|d| parse_save(&d)) {
+            Ok(save) => return Ok((save, candidate)),
+            Err(e) => errors.push(format!("{} : {}", candidate.display(), e)),
+        }
+    }
+
+    Err(errors)
+}
+
+pub fn load_or_create_game_state(location: &SaveLocation) -> (GameState, Option<SaveRecovery>) {
+    location.adopt_legacy_save();
+    let save_path = location.path();
+
+    match read_save(&save_path) {
+        Ok((save, used)) => {
+            let state = save.into_game_state();
+            println!("💾 Sauvegarde chargée et mise à jour : {} cookies, {} CPS, {} CPC",
+                     state.cookies, state.cookies_per_second, state.cookies_per_click);
+
+            let recovery = (used != save_path).then(|| {
+                eprintln!("⚠️ Sauvegarde principale illisible, restaurée depuis {}", used.display());
+                SaveRecovery { file: used.display().to_string() }
+            });
+            return (state, recovery);
+        }
+        Err(errors) => {
+            for e in &errors {
+                eprintln!("❌ Sauvegarde illisible : {}", e);
+            }
+            // Keep the unreadable file aside instead of letting the next auto-save rotate it away.
+            if !errors.is_empty() {
+                let mut name = save_path.as_os_str().to_owned();
+                name.push(format!(".corrupt-{}", now_secs()));
+                let _ = fs::rename(&save_path, PathBuf::from(name));
+            }
+        }
+    }
+
+    let powerups = get_powerups();
+    let upgrades = get_upgrade();
+    let achievements = get_achievements();
+
+    println!("✨ Nouvelle partie créée: {} powerups load {} builds load", powerups.len(), upgrades.len());
+    let state = GameState {
+        upgrades,
+        powerups,
+        achievements,
+        run: RunStats { started: now_secs(), ..RunStats::default() },
+        ..GameState::default()
+    };
+    (state, None)
+}
+
+pub fn save_game_state(game_state: &mut GameState, location: &SaveLocation) {
+    game_state.last_saved = now_secs();
+    let result = fs::create_dir_all(&location.dir)
+        .map_err(|e| e.to_string())
+        .and_then(|_| write_save(&location.path(), &SaveData::from_game_state(game_state)));
+    if let Err(e) = result {
+        eprintln!("❌ Erreur de sauvegarde : {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::BuyAmount;
+
+    const FIXTURES: &[(u32, &str)] = &[
+        (1, include_str!("../tests/fixtures/save_v1.json")),
+        (2, include_str!("../tests/fixtures/save_v2.json")),
+        (3, include_str!("../tests/fixtures/save_v3.json")),
+        (4, include_str!("../tests/fixtures/save_v4.json")),
+    ];
+
+    #[test]
+    fn every_historical_version_has_a_fixture() {
+        let versions: Vec<u32> = FIXTURES.iter().map(|(v, _)| *v).collect();
+        assert_eq!(versions, (1..=SAVE_VERSION).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn fixtures_load_without_losing_progress() {
+        for (version, data) in FIXTURES {
+            let save = parse_save(data).unwrap_or_else(|e| panic!("v{}: {}", version, e));
+            assert_eq!(save.save_version, SAVE_VERSION, "v{}", version);
+            assert_eq!(save.cookies, BigNum::from(123_456_789_012_345_678_901_234_u128), "v{}", version);
+            assert_eq!(save.lifetime_cookies, BigNum::from(987_654_321_098_765_432_109_876_u128), "v{}", version);
+            assert_eq!(save.prestige_level, 3, "v{}", version);
+            assert_eq!(save.prestige_points, BigNum::from(42.0), "v{}", version);
+            assert_eq!(save.combo, 7, "v{}", version);
+
+            let state = save.into_game_state();
+            let count = |id: &str| state.building(id).unwrap().count;
+            assert_eq!(count("curseur"), 25, "v{}", version);
+            assert_eq!(count("grand_mere"), 10, "v{}", version);
+            assert_eq!(count("ferme"), 0, "v{}", version);
+            assert_eq!(state.powerups[state.powerup_index("double_clic").unwrap()].count, 4);
+            assert_eq!(state.upgrades.len(), get_upgrade().len());
+
+            let unlocked: Vec<&str> = state.achievements.achievements.iter()
+                .zip(&state.achievements.unlocked)
+                .filter(|(_, u)| **u)
+                .map(|(a, _)| a.id.as_str())
+                .collect();
+            assert_eq!(unlocked, ["premiers_pas", "doigt_qui_chauffe"], "v{}", version);
+        }
+    }
+
+    #[test]
+    fn legacy_names_map_to_catalog_ids() {
+        let state = GameState::default();
+        let entries = state.upgrades.iter().map(|u| (&u.name, &u.id))
+            .chain(state.powerups.iter().map(|p| (&p.name, &p.id)))
+            .chain(state.research.iter().map(|r| (&r.name, &r.id)))
+            .chain(state.achievements.achievements.iter().map(|a| (&a.name, &a.id)));
+        for (name, id) in entries {
+            assert_eq!(&legacy_id(name), id, "{}", name);
+        }
+    }
+
+    #[test]
+    fn v3_research_migrates_to_ids() {
+        let v3 = r#"{ "save_version": 3, "research": ["Outils affûtés : Curseur", "Blé de grand-mère"] }"#;
+        let save = parse_save(v3).unwrap();
+        assert_eq!(save.research, ["outils_affutes_curseur", "ble_de_grand_mere"]);
+    }
+
+    #[test]
+    fn round_trip_keeps_everything() {
+        let mut state = GameState {
+            cookies: BigNum::new(3.3, 4_000),
+            prestige_points: BigNum::from(12.0),
+            ..GameState::default()
+        };
+        state.upgrades[3].count = 8;
+        state.research[2].bought = true;
+        state.achievements.unlocked[5] = true;
+        state.heavenly[0].bought = true;
+        state.challenge = Some("sans_or".into());
+        state.challenges_completed.push("sans_clic".into());
+
+        let saved = SaveData::from_game_state(&state);
+        let data = serde_json::to_string(&saved).unwrap();
+        assert_eq!(parse_save(&data).unwrap(), saved);
+        let loaded = saved.into_game_state();
+        assert!(loaded.heavenly[0].bought);
+        assert!(!loaded.golden_allowed());
+        assert_eq!(loaded.challenge_production(), 1.1);
+    }
+
+    #[test]
+    fn loading_gives_the_same_production_as_playing() {
+        let mut state = GameState { prestige_level: 4, cookies: BigNum::from(1e9), ..GameState::default() };
+        assert!(state.buy_building(0, BuyAmount::Hundred));
+        assert!(state.buy_building(2, BuyAmount::Ten));
+        assert!(state.buy_powerup(1, BuyAmount::Ten));
+        assert!(state.sell_building(2, 0.5).is_some());
+        let research = state.research_index("outils_affutes_curseur").unwrap();
+        assert!(state.buy_research(research));
+
+        let loaded = SaveData::from_game_state(&state).into_game_state();
+        assert_eq!(loaded.cookies_per_second, state.cookies_per_second);
+        assert_eq!(loaded.cookies_per_click, state.cookies_per_click);
+    }
+
+    #[test]
+    fn running_buffs_are_saved_and_unknown_ones_dropped() {
+        let mut state = GameState::default();
+        state.upgrades[0].count = 5;
+        state.add_buff(crate::buffs::FRENZY.grant(BuffTarget::Production));
+        state.add_buff(crate::buffs::CLOT.grant(BuffTarget::Production));
+        state.tick_buffs(10.0);
+
+        let mut saved = SaveData::from_game_state(&state);
+        let loaded = saved.clone().into_game_state();
+        assert_eq!(loaded.buffs, state.buffs);
+        assert_eq!(loaded.cookies_per_second, state.cookies_per_second);
+
+        saved.buffs[0].id = "disparu".into();
+        assert_eq!(saved.into_game_state().buffs.len(), 1);
+    }
+
+    #[test]
+    fn rejects_saves_from_the_future() {
+        let data = format!("{{\"save_version\": {}}}", SAVE_VERSION + 1);
+        assert!(parse_save(&data).is_err());
+    }
+
+    fn temp_save_path(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cookie_save_{}_{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("slot_1.json")
+    }
+
+    fn save_with_cookies(cookies: u128) -> SaveData {
+        SaveData { save_version: SAVE_VERSION, cookies: BigNum::from(cookies), ..SaveData::default() }
+    }
+
+    #[test]
+    fn tampered_save_fails_checksum() {
+        let data = encode_save(&save_with_cookies(10)).unwrap();
+        assert_eq!(parse_save(&data).unwrap().cookies, BigNum::from(10.0));
+
+        let tampered = data.replace("\"cookies\": \"1e1\"", "\"cookies\": \"1e4\"");
+        assert_ne!(tampered, data);
+        assert!(parse_save(&tampered).is_err());
+    }
+
+    #[test]
+    fn backups_rotate_and_keep_the_newest() {
+        let path = temp_save_path("rotate");
+        for cookies in 1..=SAVE_BACKUPS as u128 + 2 {
+            write_save(&path, &save_with_cookies(cookies)).unwrap();
+        }
+
+        let newest = SAVE_BACKUPS as u128 + 2;
+        for n in 1..=SAVE_BACKUPS {
+            let (save, _) = read_save(&backup_path(&path, n)).unwrap();
+            assert_eq!(save.cookies, BigNum::from(newest - n as u128));
+        }
+        assert!(!backup_path(&path, SAVE_BACKUPS + 1).exists());
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn corrupted_save_falls_back_to_newest_valid_backup() {
+        let path = temp_save_path("fallback");
+        for cookies in [1, 2, 3] {
+            write_save(&path, &save_with_cookies(cookies)).unwrap();
+        }
+
+        fs::write(&path, "{\"cookies\": 3, \"buil").unwrap();
+        let (save, used) = read_save(&path).unwrap();
+        assert_eq!(save.cookies, BigNum::from(2.0));
+        assert_eq!(used, backup_path(&path, 1));
+
+        fs::write(backup_path(&path, 1), "").unwrap();
+        let (save, used) = read_save(&path).unwrap();
+        assert_eq!(save.cookies, BigNum::from(1.0));
+        assert_eq!(used, backup_path(&path, 2));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn save_dir_flag_overrides_env_and_slot_is_clamped() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let location = SaveLocation::from_args(args(&["game", "--save-dir", "/tmp/cookies", "--slot", "2"]));
+        assert_eq!(location.dir, PathBuf::from("/tmp/cookies"));
+        assert_eq!(location.slot, 2);
+        assert_eq!(location.path(), PathBuf::from("/tmp/cookies/slot_2.json"));
+
+        let location = SaveLocation::from_args(args(&["game", "--save-dir", "/tmp/cookies", "--slot", "99"]));
+        assert_eq!(location.slot, SAVE_SLOTS);
+    }
+
+    #[test]
+    fn export_import_round_trip() {
+        let state = GameState {
+            cookies: BigNum::new(9.87, 654),
+            prestige_level: 4,
+            ..GameState::default()
+        };
+        let save = SaveData::from_game_state(&state);
+
+        let text = export_save(&save).unwrap();
+        assert!(text.starts_with(EXPORT_PREFIX));
+        assert!(text.len() < encode_save(&save).unwrap().len());
+        assert_eq!(import_save(&format!("  {}\n", text)).unwrap(), save);
+    }
+
+    #[test]
+    fn import_rejects_garbage_and_corruption() {
+        assert!(import_save("hello").is_err());
+        assert!(import_save("CKE1.!!!").is_err());
+
+        let text = export_save(&save_with_cookies(5)).unwrap();
+        let mut corrupted = text.clone().into_bytes();
+        let last = corrupted.len() - 3;
+        corrupted[last] = if corrupted[last] == b'A' { b'B' } else { b'A' };
+        assert!(import_save(&String::from_utf8(corrupted).unwrap()).is_err());
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&vec![b' '; MAX_IMPORT_BYTES as usize * 2]).unwrap();
+        let bomb = format!("{}{}", EXPORT_PREFIX, URL_SAFE_NO_PAD.encode(encoder.finish().unwrap()));
+        assert_eq!(import_save(&bomb).unwrap_err(), "sauvegarde trop volumineuse");
+    }
+
+    #[test]
+    fn import_migrates_old_saves() {
+        let v1 = include_str!("../tests/fixtures/save_v1.json");
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(v1.as_bytes()).unwrap();
+        let text = format!("{}{}", EXPORT_PREFIX, URL_SAFE_NO_PAD.encode(encoder.finish().unwrap()));
+
+        let save = import_save(&text).unwrap();
+        assert_eq!(save.save_version, SAVE_VERSION);
+        assert_eq!(save.prestige_points, BigNum::from(42.0));
+    }
+}
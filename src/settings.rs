@@ -0,0 +1,368 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::locale::{self, tr, tr_with, Language};
+use crate::save::write_temp_file;
+use crate::utils::{self, Notation};
+
+/// Next to the slots, shared by all of them.
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Choices offered for the auto-save interval, in seconds.
+pub const AUTOSAVE_INTERVALS: [u64; 4] = [5, 15, 30, 60];
+/// Volume goes up by this much per press, then wraps to silence.
+const VOLUME_STEP: f32 = 0.25;
+/// Sliders snap to this.
+const SLIDER_STEP: f32 = 0.05;
+
+/// Floating `+N` numbers over the cookie.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum PopupStyle {
+    /// Scattered and coloured by combo.
+    #[default]
+    Full,
+    /// Straight up, no spread.
+    Minimal,
+    Off,
+}
+
+impl PopupStyle {
+    pub const ALL: [PopupStyle; 3] = [PopupStyle::Full, PopupStyle::Minimal, PopupStyle::Off];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            PopupStyle::Full => "settings.popups.full",
+            PopupStyle::Minimal => "settings.popups.minimal",
+            PopupStyle::Off => "settings.popups.off",
+        }
+    }
+}
+
+/// Player preferences. Unlike `GameState` they don't belong to a slot or an export.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+    pub language: Language,
+    pub notation: Notation,
+    pub autosave_seconds: u64,
+    pub popups: PopupStyle,
+    /// Announces each golden cookie with a popup.
+    pub golden_alert: bool,
+    /// Volumes from 0 to 1. Effects and music are both scaled by the master volume.
+    #[serde(alias = "volume")]
+    pub master_volume: f32,
+    pub sfx_volume: f32,
+    pub music_volume: f32,
+    pub muted: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            language: Language::default(),
+            notation: Notation::default(),
+            autosave_seconds: AUTOSAVE_INTERVALS[0],
+            popups: PopupStyle::default(),
+            golden_alert: false,
+            master_volume: 0.5,
+            sfx_volume: 0.75,
+            music_volume: 0.5,
+            muted: false,
+        }
+    }
+}
+
+/// One line of the settings menu.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Setting {
+    Language,
+    Notation,
+    AutoSave,
+    Popups,
+    GoldenAlert,
+    MasterVolume,
+    SfxVolume,
+    MusicVolume,
+    Mute,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 9] = [
+        Setting::Language,
+        Setting::Notation,
+        Setting::AutoSave,
+        Setting::Popups,
+        Setting::GoldenAlert,
+        Setting::MasterVolume,
+        Setting::SfxVolume,
+        Setting::MusicVolume,
+        Setting::Mute,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Setting::Language => "settings.language",
+            Setting::Notation => "settings.notation",
+            Setting::AutoSave => "settings.autosave",
+            Setting::Popups => "settings.popups",
+            Setting::GoldenAlert => "settings.golden_alert",
+            Setting::MasterVolume => "settings.volume.master",
+            Setting::SfxVolume => "settings.volume.sfx",
+            Setting::MusicVolume => "settings.volume.music",
+            Setting::Mute => "settings.mute",
+        }
+    }
+
+    /// Shown as a slider rather than a button.
+    pub fn is_volume(self) -> bool {
+        matches!(self, Setting::MasterVolume | Setting::SfxVolume | Setting::MusicVolume)
+    }
+}
+
+fn next_in<T: Copy + PartialEq>(all: &[T], current: T) -> T {
+    let index = all.iter().position(|v| *v == current).unwrap_or(0);
+    all[(index + 1) % all.len()]
+}
+
+impl Settings {
+    /// Moves `setting` to its next value, wrapping around.
+    pub fn cycle(&mut self, setting: Setting) {
+        match setting {
+            Setting::Language => self.language = self.language.next(),
+            Setting::Notation => self.notation = self.notation.next(),
+            Setting::AutoSave => self.autosave_seconds = next_in(&AUTOSAVE_INTERVALS, self.autosave_seconds),
+            Setting::Popups => self.popups = next_in(&PopupStyle::ALL, self.popups),
+            Setting::GoldenAlert => self.golden_alert = !self.golden_alert,
+            Setting::Mute => self.muted = !self.muted,
+            Setting::MasterVolume | Setting::SfxVolume | Setting::MusicVolume => {
+                let steps = (1.0 / VOLUME_STEP).round();
+                let step = (self.volume(setting) / VOLUME_STEP).round() + 1.0;
+                self.set_volume(setting, if step > steps { 0.0 } else { step * VOLUME_STEP });
+            }
+        }
+    }
+
+    /// Value of a volume setting, 0 for any other one.
+    pub fn volume(&self, setting: Setting) -> f32 {
+        match setting {
+            Setting::MasterVolume => self.master_volume,
+            Setting::SfxVolume => self.sfx_volume,
+            Setting::MusicVolume => self.music_volume,
+            _ => 0.0,
+        }
+    }
+
+    /// Sets a volume setting, clamped and snapped to the slider steps.
+    pub fn set_volume(&mut self, setting: Setting, value: f32) {
+        let steps = (1.0 / SLIDER_STEP).round();
+        let value = (value.clamp(0.0, 1.0) * steps).round() / steps;
+        match setting {
+            Setting::MasterVolume => self.master_volume = value,
+            Setting::SfxVolume => self.sfx_volume = value,
+            Setting::MusicVolume => self.music_volume = value,
+            _ => {}
+        }
+    }
+
+    /// Volume sound effects play at.
+    pub fn sfx_gain(&self) -> f32 {
+        if self.muted { 0.0 } else { self.master_volume * self.sfx_volume }
+    }
+
+    pub fn music_gain(&self) -> f32 {
+        if self.muted { 0.0 } else { self.master_volume * self.music_volume }
+    }
+
+    /// Current value of `setting`, as shown in the menu.
+    pub fn label(&self, setting: Setting) -> String {
+        match setting {
+            Setting::Language => tr("ui.language"),
+            Setting::Notation => tr(self.notation.key()),
+            Setting::AutoSave => tr_with("settings.seconds", &[("seconds", self.autosave_seconds.to_string())]),
+            Setting::Popups => tr(self.popups.key()),
+            Setting::GoldenAlert => tr(if self.golden_alert { "settings.on" } else { "settings.off" }),
+            Setting::MasterVolume | Setting::SfxVolume | Setting::MusicVolume => {
+                format!("{}%", (self.volume(setting) * 100.0).round())
+            }
+            Setting::Mute => tr(if self.muted { "settings.on" } else { "settings.off" }),
+        }
+    }
+
+    /// Makes the language and notation current. Names and descriptions are written
+    /// as the catalogs are read, so this comes before loading a save.
+    pub fn apply(&self) {
+        locale::set_language(self.language);
+        utils::set_notation(self.notation);
+    }
+
+    /// Reads `settings.json` from `dir`, or the defaults if there is none or it is broken.
+    pub fn load(dir: &Path) -> Self {
+        let path = settings_path(dir);
+        let Ok(data) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        let mut settings: Self = serde_json::from_str(&data).unwrap_or_else(|e| {
+            eprintln!("❌ Réglages illisibles ({}), valeurs par défaut : {}", path.display(), e);
+            Self::default()
+        });
+        settings.sanitize();
+        settings
+    }
+
+    /// Like `load`, but the first time, with no `settings.json` yet, the language
+    /// and notation come from `save`: they were stored in the saves before.
+    pub fn load_or_migrate(dir: &Path, save: &Path) -> Self {
+        if settings_path(dir).exists() {
+            return Self::load(dir);
+        }
+
+        let Some(legacy) = fs::read_to_string(save)
+            .ok()
+            .and_then(|data| serde_json::from_str::<LegacyPreferences>(&data).ok())
+        else {
+            return Self::default();
+        };
+        let mut settings = Self::default();
+        if let Some(language) = legacy.language {
+            settings.language = language;
+        }
+        if let Some(notation) = legacy.notation {
+            settings.notation = notation;
+        }
+
+        // The next save no longer holds them, so keep them now.
+        if let Err(e) = settings.save(dir) {
+            eprintln!("❌ Réglages non sauvegardés : {}", e);
+        }
+        settings
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), String> {
+        let data = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        let path = settings_path(dir);
+        let tmp = write_temp_file(&path, &data)?;
+        fs::rename(&tmp, &path).map_err(|e| e.to_string())
+    }
+
+    /// Brings hand-edited values back to ones the menu offers.
+    fn sanitize(&mut self) {
+        let autosave = self.autosave_seconds;
+        self.autosave_seconds = AUTOSAVE_INTERVALS.iter()
+            .copied()
+            .min_by_key(|interval| interval.abs_diff(autosave))
+            .unwrap_or(AUTOSAVE_INTERVALS[0]);
+        for setting in [Setting::MasterVolume, Setting::SfxVolume, Setting::MusicVolume] {
+            self.set_volume(setting, self.volume(setting));
+        }
+    }
+}
+
+/// Preferences saves carried before `settings.json` existed.
+#[derive(Deserialize)]
+struct LegacyPreferences {
+    language: Option<Language>,
+    notation: Option<Notation>,
+}
+
+fn settings_path(dir: &Path) -> PathBuf {
+    dir.join(SETTINGS_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_setting_cycles_back_to_its_start() {
+        for setting in Setting::ALL {
+            let start = Settings::default();
+            let mut settings = start.clone();
+            let mut seen = 0;
+            loop {
+                settings.cycle(setting);
+                seen += 1;
+                if settings == start || seen > 10 {
+                    break;
+                }
+            }
+            assert_eq!(settings, start, "{:?}", setting);
+            assert!(seen > 1, "{:?}", setting);
+        }
+    }
+
+    #[test]
+    fn settings_round_trip_and_survive_garbage() {
+        let dir = std::env::temp_dir().join(format!("cookie_settings_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(Settings::load(&dir), Settings::default());
+
+        let settings = Settings {
+            language: Language::En,
+            notation: Notation::Scientific,
+            autosave_seconds: 30,
+            popups: PopupStyle::Off,
+            golden_alert: true,
+            master_volume: 0.75,
+            sfx_volume: 0.25,
+            music_volume: 0.0,
+            muted: true,
+        };
+        settings.save(&dir).unwrap();
+        assert_eq!(Settings::load(&dir), settings);
+
+        // Older or hand-edited files keep whatever they do set.
+        fs::write(settings_path(&dir), r#"{"autosave_seconds": 60, "volume": 0.25}"#).unwrap();
+        assert_eq!(Settings::load(&dir).autosave_seconds, 60);
+        assert_eq!(Settings::load(&dir).master_volume, 0.25);
+
+        fs::write(settings_path(&dir), r#"{"autosave_seconds": 0, "sfx_volume": 3.0, "music_volume": -1.0}"#).unwrap();
+        let clamped = Settings::load(&dir);
+        assert_eq!(clamped.autosave_seconds, AUTOSAVE_INTERVALS[0]);
+        assert_eq!(clamped.sfx_volume, 1.0);
+        assert_eq!(clamped.music_volume, 0.0);
+        fs::write(settings_path(&dir), r#"{"autosave_seconds": 1000}"#).unwrap();
+        assert_eq!(Settings::load(&dir).autosave_seconds, 60);
+
+        fs::write(settings_path(&dir), "pas du json").unwrap();
+        assert_eq!(Settings::load(&dir), Settings::default());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn language_and_notation_move_out_of_old_saves() {
+        let dir = std::env::temp_dir().join(format!("cookie_settings_migrate_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let save = dir.join("slot_1.json");
+        fs::write(&save, r#"{"save_version": 4, "cookies": "1e3", "language": "En", "notation": "Scientific"}"#).unwrap();
+
+        let settings = Settings::load_or_migrate(&dir, &save);
+        assert_eq!(settings.language, Language::En);
+        assert_eq!(settings.notation, Notation::Scientific);
+        assert!(settings_path(&dir).exists());
+
+        // From then on settings.json wins.
+        fs::write(&save, r#"{"save_version": 4, "language": "Fr"}"#).unwrap();
+        assert_eq!(Settings::load_or_migrate(&dir, &save).language, Language::En);
+        assert_eq!(Settings::load_or_migrate(&dir.join("vide"), &dir.join("absent.json")), Settings::default());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn volumes_mix_and_mute() {
+        let mut settings = Settings::default();
+        settings.set_volume(Setting::MasterVolume, 0.5);
+        settings.set_volume(Setting::SfxVolume, 0.43);
+        settings.set_volume(Setting::MusicVolume, 2.0);
+        assert_eq!(settings.sfx_volume, 0.45);
+        assert_eq!(settings.music_volume, 1.0);
+        assert!((settings.sfx_gain() - 0.225).abs() < 1e-6);
+        assert_eq!(settings.music_gain(), 0.5);
+
+        settings.cycle(Setting::Mute);
+        assert_eq!(settings.sfx_gain(), 0.0);
+        assert_eq!(settings.music_gain(), 0.0);
+    }
+}
@@ -0,0 +1,250 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::cmp::Ordering;
+use std::time::Duration;
+use crate::bignum::BigNum;
+use crate::resources::{BuyAmount, GameState};
+use crate::system::passive_income_system;
+use crate::utils::{format_duration, format_number};
+
+/// Purchase strategy used by the headless simulation.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Strategy {
+    /// Always buy the cheapest affordable item.
+    Cheapest,
+    /// Buy whatever pays itself back the fastest, waiting for it if needed.
+    Payback,
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct SimConfig {
+    pub duration: f64,
+    pub step: f64,
+    pub clicks_per_second: f64,
+    pub strategy: Strategy,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            duration: 24.0 * 3_600.0,
+            step: 1.0,
+            clicks_per_second: 5.0,
+            strategy: Strategy::Payback,
+        }
+    }
+}
+
+impl SimConfig {
+    /// `--hours <h>`, `--step <seconds>`, `--cps-clicks <n>`, `--strategy cheapest|payback`.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Self {
+        let mut config = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().and_then(|v| v.parse::<f64>().ok());
+            match arg.as_str() {
+                "--hours" => config.duration = value().map_or(config.duration, |h| h * 3_600.0),
+                "--step" => config.step = value().unwrap_or(config.step).max(0.01),
+                "--cps-clicks" => config.clicks_per_second = value().unwrap_or(config.clicks_per_second),
+                "--strategy" => {
+                    config.strategy = match args.next().as_deref() {
+                        Some("cheapest") => Strategy::Cheapest,
+                        _ => Strategy::Payback,
+                    }
+                }
+                _ => {}
+            }
+        }
+        config
+    }
+}
+
+/// What happened during the run, in simulated seconds.
+#[derive(Resource, Default, Debug)]
+pub struct SimReport {
+    pub elapsed: f64,
+    pub click_buffer: f64,
+    /// (total cookies earned, time reached, CPS at that time)
+    pub cookie_milestones: Vec<(BigNum, f64, BigNum)>,
+    /// (building index, time of the first purchase)
+    pub first_buildings: Vec<(usize, f64)>,
+}
+
+const COOKIE_MILESTONES: [f64; 8] = [1e3, 1e6, 1e9, 1e12, 1e15, 1e18, 1e21, 1e24];
+
+pub fn run_headless() {
+    let config = SimConfig::from_args(std::env::args());
+    println!(
+        "🧪 Simulation : {} à pas de {}s, {} clics/s, stratégie {:?}",
+        format_duration(config.duration as u64),
+        config.step,
+        config.clicks_per_second,
+        config.strategy,
+    );
+
+    let report = simulate(config);
+    print_report(&report);
+}
+
+pub fn simulate(config: SimConfig) -> SimReport {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(config.step)))
+        .insert_resource(GameState::default())
+        .insert_resource(SimReport::default())
+        .insert_resource(config.clone())
+        .add_systems(
+            Update,
+            (sim_click_system, passive_income_system, sim_purchase_system, sim_milestone_system).chain(),
+        );
+
+    // Virtual time clamps each frame to 250ms by default, which would slow the run down.
+    app.world_mut()
+        .resource_mut::<Time<Virtual>>()
+        .set_max_delta(Duration::from_secs_f64(config.step));
+
+    while app.world().resource::<SimReport>().elapsed < config.duration {
+        app.update();
+    }
+
+    app.world_mut().remove_resource::<SimReport>().unwrap_or_default()
+}
+
+fn sim_click_system(
+    mut game_state: ResMut<GameState>,
+    mut report: ResMut<SimReport>,
+    config: Res<SimConfig>,
+    time: Res<Time>,
+) {
+    report.elapsed += time.delta_seconds_f64();
+    report.click_buffer += config.clicks_per_second * time.delta_seconds_f64();
+
+    let clicks = report.click_buffer.floor();
+    report.click_buffer -= clicks;
+
+    // Crit rolls walk a low-discrepancy sequence so runs stay reproducible.
+    for _ in 0..clicks as u64 {
+        let roll = (game_state.click_count as f64 * 0.618_033_988_7).fract() as f32;
+        game_state.click(0, roll);
+    }
+}
+
+fn sim_purchase_system(
+    mut game_state: ResMut<GameState>,
+    mut report: ResMut<SimReport>,
+    config: Res<SimConfig>,
+) {
+    // Keep buying as long as the strategy finds something affordable this tick.
+    while let Some(item) = pick_purchase(&game_state, &config) {
+        let bought = match item {
+            Purchase::Building(index) => {
+                let first = game_state.upgrades[index].count == 0;
+                let bought = game_state.buy_building(index, BuyAmount::One);
+                if bought && first {
+                    let elapsed = report.elapsed;
+                    report.first_buildings.push((index, elapsed));
+                }
+                bought
+            }
+            Purchase::PowerUp(index) => game_state.buy_powerup(index, BuyAmount::One),
+            Purchase::Research(index) => game_state.buy_research(index),
+        };
+        if !bought {
+            break;
+        }
+    }
+}
+
+fn sim_milestone_system(game_state: Res<GameState>, mut report: ResMut<SimReport>) {
+    let reached = report.cookie_milestones.len();
+    if let Some(milestone) = COOKIE_MILESTONES.get(reached).map(|&m| BigNum::from(m))
+        && game_state.total_cookies_earned >= milestone
+    {
+        let elapsed = report.elapsed;
+        report.cookie_milestones.push((milestone, elapsed, game_state.cookies_per_second));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Purchase {
+    Building(usize),
+    PowerUp(usize),
+    Research(usize),
+}
+
+fn pick_purchase(game_state: &GameState, config: &SimConfig) -> Option<Purchase> {
+    let buildings = game_state.upgrades.iter().enumerate()
+        .map(|(i, u)| (Purchase::Building(i), u.cost, game_state.building_cps(i)));
+    let powerups = game_state.powerups.iter().enumerate()
+        .map(|(i, p)| (Purchase::PowerUp(i), p.cost, powerup_gain(game_state, i) * config.clicks_per_second));
+    let research = (0..game_state.research.len())
+        .filter(|&i| game_state.research_available(i))
+        .map(|i| (Purchase::Research(i), game_state.research[i].cost, research_gain(game_state, i)));
+    let candidates = buildings.chain(powerups).chain(research).filter(|(_, _, gain)| *gain > BigNum::ZERO);
+
+    let choice = match config.strategy {
+        Strategy::Cheapest => candidates.min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal)),
+        Strategy::Payback => candidates.min_by(|a, b| {
+            (a.1 / a.2).partial_cmp(&(b.1 / b.2)).unwrap_or(Ordering::Equal)
+        }),
+    };
+
+    choice
+        .filter(|(_, cost, _)| game_state.cookies >= *cost)
+        .map(|(item, _, _)| item)
+}
+
+/// Average click value gained by buying one unit of power-up `index`.
+fn powerup_gain(game_state: &GameState, index: usize) -> BigNum {
+    let mut next = game_state.clone();
+    next.powerups[index].count += 1;
+    next.refresh_production();
+    next.expected_click() - game_state.expected_click()
+}
+
+/// CPS gained by buying research `index`.
+fn research_gain(game_state: &GameState, index: usize) -> BigNum {
+    let mut next = game_state.clone();
+    next.research[index].bought = true;
+    next.refresh_production();
+    next.cookies_per_second - game_state.cookies_per_second
+}
+
+fn print_report(report: &SimReport) {
+    println!("\n{:<14} {:>14} {:>14}", "Cookies", "Temps", "CPS");
+    for (milestone, time, cps) in &report.cookie_milestones {
+        println!(
+            "{:<14} {:>14} {:>14}",
+            format_number(*milestone),
+            format_duration(*time as u64),
+            format_number(*cps),
+        );
+    }
+
+    let names = crate::upgrades::get_upgrade();
+    println!("\n{:<26} {:>14}", "Premier bâtiment", "Temps");
+    for (index, time) in &report.first_buildings {
+        println!("{:<26} {:>14}", names[*index].name, format_duration(*time as u64));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_hour_run_reaches_early_milestones() {
+        let report = simulate(SimConfig {
+            duration: 3_600.0,
+            step: 4.0,
+            ..SimConfig::default()
+        });
+
+        assert!(report.elapsed >= 3_600.0);
+        assert!(report.cookie_milestones.first().is_some_and(|(m, _, _)| *m == BigNum::from(1e3)));
+        assert!(report.first_buildings.iter().any(|(index, _)| *index == 0));
+
+        let times: Vec<f64> = report.cookie_milestones.iter().map(|(_, t, _)| *t).collect();
+        assert!(times.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
@@ -1,15 +1,28 @@
 use crate::achievements::AchievementRequirement;
-use bevy::input::InputSystem;
 use bevy::prelude::*;
 use crate::components::*;
 use crate::resources::*;
+use crate::save::{export_save, import_save, load_or_create_game_state, save_game_state, PendingImport, SaveData, SaveLocation, SaveRecovery};
+use crate::ui::{section_title, shop_ids, spawn_buff_row, spawn_challenge_choices, spawn_confirm_dialog, spawn_confirm_dialog_with, spawn_heavenly_menu, spawn_run_summary, spawn_settings_menu, spawn_shop_items};
+use crate::catalog::{loaded_catalog, CatalogHandles, ContentPack};
+use crate::ui_fonts::UiFonts;
+use bevy::window::FileDragAndDrop;
+use std::fs;
 use crate::ui::spawn_achievement_popup;
 use crate::utils::*;
+use crate::locale::{language, tr, tr_count, tr_with};
+use crate::economy::{ClickOutcome, PRESTIGE_BUFF};
+use crate::bignum::BigNum;
+use crate::settings::{PopupStyle, Settings};
+use crate::audio::{PlaySound, Sfx};
+use crate::golden::{GoldenEffect, GoldenOutcome};
+use crate::challenges::challenge_def;
+use bevy::ui::RelativeCursorPosition;
+use std::time::Duration;
 
 const CLICKS_PER_COMBO: u128 = 10;
 
-pub const PRESTIGE_BUFF: f64 = 0.01;
-
+#[allow(clippy::type_complexity)]
 pub fn cookie_click_system(
     mut commands: Commands,
     mut interaction_query: Query<
@@ -17,21 +30,21 @@ pub fn cookie_click_system(
         (Changed<Interaction>, With<Cookie>),
     >,
     mut game_state: ResMut<GameState>,
-    click_power: Res<ClickPower>,
     mut combo: ResMut<ComboSystem>,
     window_query: Query<&Window>,
+    settings: Res<Settings>,
+    mut sounds: EventWriter<PlaySound>,
 ) {
     for (interaction, mut color, node, transform, mut scale) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
-                let base_earned = click_power.0;
-                let combo_mult = if combo.active { 1 + combo.combo } else { 1 };
-                let earned = base_earned * combo_mult;
-
-                game_state.cookies += earned;
-                game_state.total_cookies_earned += earned;
-                game_state.lifetime_cookies += earned;
-                game_state.click_count += 1;
+                // Challenges without clicks leave the cookie inert.
+                if !game_state.can_click() {
+                    *color = Color::srgb(0.5, 0.4, 0.3).into();
+                    continue;
+                }
+                let combo_level = if combo.active { combo.combo } else { 0 };
+                let outcome = game_state.click(combo_level, pseudo_random());
 
                 combo.clicks += 1;
                 combo.timer.reset();
@@ -41,10 +54,11 @@ pub fn cookie_click_system(
                 combo.combo = combo.clicks / CLICKS_PER_COMBO;
                 // only for achievements
                 game_state.combo = combo.combo;
+                game_state.run.best_combo = game_state.run.best_combo.max(combo.combo);
 
                 let combo_up = combo.combo > previous_combo;
-
-                let earned = base_earned * combo_mult;
+                let loud = combo_up || outcome.critical;
+                sounds.send(PlaySound(if loud { Sfx::ComboUp } else { Sfx::Click }));
 
                 *color = Color::srgb(1.0, 0.7, 0.3).into();
                 scale.pulse = 1.0;
@@ -53,7 +67,7 @@ pub fn cookie_click_system(
                 let window_height = window.height();
                 let cookie_pos = transform.translation();
 
-                spawn_popup(&mut commands, earned, cookie_pos, node, window_height, combo_up);
+                spawn_popup(&mut commands, outcome, cookie_pos, node, window_height, combo_up, settings.popups);
                 // spawn_particles(&mut commands, cookie_pos, node, window_height);
             }
             Interaction::Hovered => {
@@ -93,36 +107,59 @@ fn random_ring(min: f32, max: f32) -> Vec2 {
 }
 
 
+/// Floating number of a click. Critical clicks stand out even in minimal mode:
+/// bigger, magenta and labelled.
 fn spawn_popup(
     commands: &mut Commands,
-    earned: u128,
+    outcome: ClickOutcome,
     cookie_pos: Vec3,
     node: &Node,
     _window_height: f32,
     is_combo: bool,
+    style: PopupStyle,
 ) {
-    let base_color = if is_combo {
+    if style == PopupStyle::Off {
+        return;
+    }
+    // Minimal popups rise straight up in a single colour.
+    let is_combo = is_combo && style == PopupStyle::Full;
+    let minimal = style == PopupStyle::Minimal;
+    let critical = outcome.critical;
+
+    let base_color = if critical {
+        Color::srgb(1.0, 0.3, 0.9)
+    } else if is_combo {
         Color::srgb(1.0, 0.35, 0.35)
     } else {
         Color::srgb(1.0, 0.9, 0.35)
     };
 
-    let radius_min = if is_combo { 20.0 } else { 12.0 };
-    let radius_max = if is_combo { 45.0 } else { 28.0 };
-    let offset = random_ring(radius_min, radius_max);
+    let radius_min = if is_combo || critical { 20.0 } else { 12.0 };
+    let radius_max = if is_combo || critical { 45.0 } else { 28.0 };
+    let offset = if minimal { Vec2::ZERO } else { random_ring(radius_min, radius_max) };
 
     let spawn_x = cookie_pos.x + node.size().x / 2.0 + offset.x;
     let spawn_y = cookie_pos.y - 70.0 + offset.y;
 
     let dir = offset.normalize_or_zero();
 
-    let impulse = if is_combo { 260.0 } else { 200.0 };
-    let velocity = Vec2::new(
-        dir.x * impulse + (pseudo_random() - 0.5) * 40.0,
-        dir.y * impulse - 120.0,
-    );
+    let impulse = if is_combo || critical { 260.0 } else { 200.0 };
+    let velocity = if minimal {
+        Vec2::new(0.0, -120.0)
+    } else {
+        Vec2::new(
+            dir.x * impulse + (pseudo_random() - 0.5) * 40.0,
+            dir.y * impulse - 120.0,
+        )
+    };
 
-    let scale = 0.85 + pseudo_random() * 0.25;
+    let scale = if minimal { 1.0 } else { 0.85 + pseudo_random() * 0.25 };
+    let font_size = if critical { 56.0 } else { 40.0 };
+    let label = if critical {
+        tr_with("popup.critical", &[("count", format_number(outcome.earned))])
+    } else {
+        format!("+{}", format_number(outcome.earned))
+    };
 
     commands.spawn((
         TextBundle {
@@ -133,9 +170,9 @@ fn spawn_popup(
                 ..default()
             },
             text: Text::from_section(
-                format!("+{}", format_number(earned as u128)),
+                label,
                 TextStyle {
-                    font_size: 40.0 , // * scale
+                    font_size, // * scale
                     color: base_color,
                     ..default()
                 },
@@ -145,8 +182,9 @@ fn spawn_popup(
             ..default()
         },
         PopupText {
-            lifetime: Timer::from_seconds(if is_combo { 1.8 } else { 1.4 }, TimerMode::Once),
+            lifetime: Timer::from_seconds(if is_combo || critical { 1.8 } else { 1.4 }, TimerMode::Once),
             velocity,
+            font_size,
         },
     ));
 }
@@ -189,25 +227,46 @@ pub fn upgrade_button_system(
     (&Interaction, &mut BackgroundColor, &mut BorderColor, &UpgradeButton),
     >,
     mut game_state: ResMut<GameState>,
+    buy_amount: Res<BuyAmount>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut sounds: EventWriter<PlaySound>,
 ) {
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
     for (interaction, mut color, mut border, upgrade_button) in &mut interaction_query {
-        if upgrade_button.upgrade_index >= game_state.upgrades.len() {
+        let Some(index) = game_state.building_index(&upgrade_button.building) else {
+            continue;
+        };
+        let Some((_, price)) = game_state.building_order(index, *buy_amount) else {
+            continue;
+        };
+        // Buildings a challenge forbids look and act like ones out of reach.
+        let can_afford = game_state.building_allowed(index) && game_state.cookies >= price;
+
+        // Right click, or shift + click, sells one unit.
+        let sell = match *interaction {
+            Interaction::Hovered => mouse.just_pressed(MouseButton::Right),
+            Interaction::Pressed => shift && mouse.just_pressed(MouseButton::Left),
+            Interaction::None => false,
+        };
+        if sell {
+            let refund = crate::catalog::current().rules.sell_refund;
+            if game_state.sell_building(index, refund).is_some() {
+                *color = Color::srgb(0.7, 0.5, 0.2).into();
+                *border = Color::srgb(1.0, 0.7, 0.3).into();
+            }
             continue;
         }
 
-        let upgrade_cost = game_state.upgrades[upgrade_button.upgrade_index].cost;
-        let upgrade_cps = game_state.upgrades[upgrade_button.upgrade_index].cps;
-        let can_afford = game_state.cookies >= upgrade_cost;
-
         match *interaction {
             Interaction::Pressed => {
-                if can_afford {
-                    game_state.cookies -= upgrade_cost;
-                    let upgrade = &mut game_state.upgrades[upgrade_button.upgrade_index];
-                    upgrade.count += 1;
-                    upgrade.cost = upgrade.calculate_cost();
-                    let multiplier = 1.0 + (game_state.prestige_level as f64 * PRESTIGE_BUFF);
-                    game_state.cookies_per_second += upgrade_cps * multiplier;
+                // Pressed stays set while the button is held: buy once per click.
+                if !shift
+                    && mouse.just_pressed(MouseButton::Left)
+                    && game_state.buy_building(index, *buy_amount)
+                {
+                    sounds.send(PlaySound(Sfx::Purchase));
                     *color = Color::srgb(0.3, 0.7, 0.4).into();
                     *border = Color::srgb(0.4, 1.0, 0.5).into();
                 }
@@ -239,25 +298,23 @@ pub fn powerup_button_system(
     (&Interaction, &mut BackgroundColor, &mut BorderColor, &PowerUpButton),
     >,
     mut game_state: ResMut<GameState>,
-    mut click_power: ResMut<ClickPower>,
+    buy_amount: Res<BuyAmount>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut sounds: EventWriter<PlaySound>,
 ) {
     for (interaction, mut color, mut border, powerup_button) in &mut interaction_query {
-        if powerup_button.powerup_index >= game_state.powerups.len() {
+        let Some(index) = game_state.powerup_index(&powerup_button.powerup) else {
             continue;
-        }
-
-        let powerup = &game_state.powerups[powerup_button.powerup_index];
-        let can_afford = game_state.cookies >= powerup.cost;
+        };
+        let Some((_, price)) = game_state.powerup_order(index, *buy_amount) else {
+            continue;
+        };
+        let can_afford = game_state.cookies >= price;
 
         match *interaction {
             Interaction::Pressed => {
-                if can_afford {
-                    game_state.cookies -= powerup.cost;
-                    let powerup = &mut game_state.powerups[powerup_button.powerup_index];
-                    powerup.count += 1;
-                    powerup.cost = powerup.calculate_cost();
-                    click_power.0 += powerup.multiplier;
-                    game_state.cookies_per_click = click_power.0;
+                if mouse.just_pressed(MouseButton::Left) && game_state.buy_powerup(index, *buy_amount) {
+                    sounds.send(PlaySound(Sfx::Purchase));
                     *color = Color::srgb(0.5, 0.3, 0.7).into();
                     *border = Color::srgb(0.7, 0.4, 1.0).into();
                 }
@@ -284,38 +341,112 @@ pub fn powerup_button_system(
     }
 }
 
+pub fn research_button_system(
+    mut interaction_query: Query<(&Interaction, &mut BackgroundColor, &mut BorderColor, &ResearchButton)>,
+    mut game_state: ResMut<GameState>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    for (interaction, mut color, mut border, research_button) in &mut interaction_query {
+        let Some(index) = game_state.research_index(&research_button.research) else {
+            continue;
+        };
+        let research = &game_state.research[index];
+        let can_afford = game_state.cookies >= research.cost;
+
+        match *interaction {
+            Interaction::Pressed => {
+                if mouse.just_pressed(MouseButton::Left) && game_state.buy_research(index) {
+                    sounds.send(PlaySound(Sfx::Purchase));
+                    println!("🔬 Recherche terminée : {}", game_state.research[index].name);
+                    *color = Color::srgb(0.3, 0.7, 0.4).into();
+                    *border = Color::srgb(0.4, 1.0, 0.5).into();
+                }
+            }
+            Interaction::Hovered => {
+                if can_afford {
+                    *color = Color::srgb(0.25, 0.4, 0.5).into();
+                    *border = Color::srgb(0.4, 0.7, 0.9).into();
+                } else {
+                    *color = Color::srgb(0.35, 0.25, 0.25).into();
+                    *border = Color::srgb(0.6, 0.3, 0.3).into();
+                }
+            }
+            Interaction::None => {
+                if can_afford {
+                    *color = Color::srgb(0.2, 0.3, 0.4).into();
+                    *border = Color::srgb(0.3, 0.4, 0.5).into();
+                } else {
+                    *color = Color::srgb(0.25, 0.15, 0.15).into();
+                    *border = Color::srgb(0.4, 0.2, 0.2).into();
+                }
+            }
+        }
+    }
+}
+
+/// Only research whose requirement is met is listed; bought ones disappear.
+pub fn update_research_ui_system(
+    game_state: Res<GameState>,
+    mut button_query: Query<(&mut Style, &ResearchButton)>,
+    mut text_query: Query<(&mut Text, &ResearchText)>,
+) {
+    for (mut style, research_button) in &mut button_query {
+        let available = game_state.research_index(&research_button.research)
+            .is_some_and(|index| game_state.research_available(index));
+        let display = if available {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if style.display != display {
+            style.display = display;
+        }
+    }
+
+    for (mut text, research_text) in &mut text_query {
+        if let Some(research) = game_state.research.iter().find(|r| r.id == research_text.research) {
+            text.sections[0].value = format!(
+                "{} {}\n{}\n💰 {}",
+                research.emoji,
+                research.name,
+                research.description,
+                format_number(research.cost)
+            );
+        }
+    }
+}
+
+/// Pressing PRESTIGE only asks: the reset happens in `confirm_dialog_system`.
 pub fn prestige_button_system(
+    mut commands: Commands,
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor),
         With<PrestigeButton>,
     >,
-    mut game_state: ResMut<GameState>,
+    dialog_query: Query<(), With<ConfirmDialog>>,
+    game_state: Res<GameState>,
+    fonts: Res<UiFonts>,
 ) {
     for (interaction, mut color) in &mut interaction_query {
-        let prestige_cookies_needed = prestige_requirement((game_state.prestige_level + 200) as u64);
-        let can_prestige = game_state.cookies >= prestige_cookies_needed;
+        let can_prestige = game_state.can_prestige();
 
         match *interaction {
             Interaction::Pressed => {
-                if can_prestige {
-                    game_state.prestige_level += 1;
-                    game_state.prestige_points = game_state.lifetime_cookies / 1_000_000;
-                    game_state.cookies = 0;
-                    game_state.total_cookies_earned = 0;
-                    game_state.cookies_per_second = 0.0;
-                    game_state.combo = 0;
-
-                    for upgrade in &mut game_state.upgrades {
-                        upgrade.count = 0;
-                        upgrade.cost = upgrade.base_cost;
-                    }
-
-                    for powerup in &mut game_state.powerups {
-                        powerup.count = 0;
-                        powerup.cost = powerup.base_cost;
-                    }
-
-                    println!("⭐ PRESTIGE! Niveau {}", game_state.prestige_level);
+                if can_prestige && dialog_query.is_empty() {
+                    let preview = game_state.prestige_preview();
+                    let body = tr_with("dialog.prestige.body", &[
+                        ("level", game_state.prestige_level.to_string()),
+                        ("next", preview.next_level.to_string()),
+                        ("multiplier", format_decimal(preview.multiplier)),
+                        ("next_multiplier", format_decimal(preview.next_multiplier)),
+                        ("chips", format_number(preview.chips)),
+                        ("cookies", format_number(game_state.cookies)),
+                    ]);
+                    commands.insert_resource(PendingPrestige::default());
+                    spawn_confirm_dialog_with(&mut commands, &fonts, DialogAction::Prestige, &tr("dialog.prestige.title"), &body, |card| {
+                        spawn_challenge_choices(card, &fonts, &game_state);
+                    });
                 }
             }
             Interaction::Hovered => {
@@ -336,37 +467,134 @@ pub fn prestige_button_system(
     }
 }
 
-pub fn passive_income_system(
-    mut game_state: ResMut<GameState>,
-    time: Res<Time>,
+/// The heavenly button opens the upgrade tree; the one inside closes it.
+pub fn heavenly_menu_system(
+    mut commands: Commands,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<HeavenlyButton>)>,
+    menu_query: Query<Entity, With<HeavenlyMenu>>,
+    fonts: Res<UiFonts>,
+    game_state: Res<GameState>,
 ) {
-    if game_state.cookies_per_second <= 0.0 {
+    if !interaction_query.iter().any(|i| *i == Interaction::Pressed) {
         return;
     }
 
-    let gained = game_state.cookies_per_second * time.delta_seconds_f64();
+    if menu_query.is_empty() {
+        spawn_heavenly_menu(&mut commands, &fonts, &game_state);
+    } else {
+        for entity in &menu_query {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn heavenly_node_button_system(
+    interaction_query: Query<(&Interaction, &HeavenlyNodeButton), Changed<Interaction>>,
+    mut game_state: ResMut<GameState>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    for (interaction, node) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(index) = game_state.heavenly_index(&node.upgrade) else {
+            continue;
+        };
+        if game_state.buy_heavenly(index) {
+            sounds.send(PlaySound(Sfx::Purchase));
+            println!("☁️ Amélioration céleste : {}", game_state.heavenly[index].name);
+        }
+    }
+}
 
-    game_state.cps_buffer += gained;
+/// Bought nodes are green, the ones that can be bought now blue, the rest dim.
+pub fn update_heavenly_ui_system(
+    game_state: Res<GameState>,
+    mut chips_query: Query<&mut Text, (With<HeavenlyChipsText>, Without<HeavenlyNodeText>)>,
+    mut button_query: Query<(&mut BackgroundColor, &mut BorderColor, &HeavenlyNodeButton)>,
+    mut text_query: Query<(&mut Text, &HeavenlyNodeText)>,
+) {
+    let chips = game_state.heavenly_chips();
+    for mut text in &mut chips_query {
+        text.sections[0].value = tr_count("ui.heavenly.chips", chips.to_f64(), &[("count", format_number(chips))]);
+    }
 
-    let earned = game_state.cps_buffer.floor() as u128;
+    for (mut color, mut border, node) in &mut button_query {
+        let Some(index) = game_state.heavenly_index(&node.upgrade) else {
+            continue;
+        };
+        let upgrade = &game_state.heavenly[index];
+        let (background, edge) = if upgrade.bought {
+            (Color::srgb(0.2, 0.4, 0.3), Color::srgb(0.4, 1.0, 0.5))
+        } else if game_state.heavenly_available(index) && chips >= upgrade.cost {
+            (Color::srgb(0.25, 0.3, 0.55), Color::srgb(0.7, 0.8, 1.0))
+        } else if game_state.heavenly_available(index) {
+            (Color::srgb(0.22, 0.22, 0.32), Color::srgb(0.4, 0.4, 0.6))
+        } else {
+            (Color::srgb(0.15, 0.15, 0.18), Color::srgb(0.25, 0.25, 0.3))
+        };
+        *color = background.into();
+        *border = edge.into();
+    }
 
-    if earned > 0 {
-        game_state.cookies += earned;
-        game_state.total_cookies_earned += earned;
-        game_state.lifetime_cookies += earned;
-        game_state.cps_buffer -= earned as f64;
+    for (mut text, node) in &mut text_query {
+        let Some(index) = game_state.heavenly_index(&node.upgrade) else {
+            continue;
+        };
+        let upgrade = &game_state.heavenly[index];
+        let status = if upgrade.bought {
+            tr("ui.heavenly.bought")
+        } else if game_state.heavenly_available(index) {
+            format!("☁ {}", format_number(upgrade.cost))
+        } else {
+            let parents: Vec<String> = upgrade.parents.iter()
+                .filter_map(|p| game_state.heavenly.iter().find(|h| &h.id == p))
+                .map(|h| h.name.clone())
+                .collect();
+            tr_with("ui.heavenly.locked", &[("parents", parents.join(", "))])
+        };
+        let value = format!("{} {}\n{}\n{}", upgrade.emoji, upgrade.name, upgrade.description, status);
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
     }
 }
 
+pub fn passive_income_system(
+    mut game_state: ResMut<GameState>,
+    time: Res<Time>,
+) {
+    game_state.tick(time.delta_seconds_f64());
+}
+
+pub fn buff_tick_system(
+    mut game_state: ResMut<GameState>,
+    time: Res<Time>,
+) {
+    game_state.tick_buffs(time.delta_seconds_f64());
+}
+
 
+#[allow(clippy::too_many_arguments)]
 pub fn golden_cookie_spawn_system(
     mut commands: Commands,
     mut timer: ResMut<GoldenCookieTimer>,
     time: Res<Time>,
-    golden_cookies: Query<&GoldenCookie>,
+    golden_cookies: Query<Entity, With<GoldenCookie>>,
     windows: Query<&Window>,
+    settings: Res<Settings>,
+    game_state: Res<GameState>,
+    mut sounds: EventWriter<PlaySound>,
 ) {
-    timer.0.tick(time.delta());
+    if !game_state.golden_allowed() {
+        // A challenge without golden cookies also takes away the ones on screen.
+        for entity in &golden_cookies {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+    // Golden rain and heavenly upgrades make the wait shorter by running the timer faster.
+    timer.0.tick(time.delta().mul_f64(game_state.golden_spawn_rate()));
     if timer.0.just_finished() && golden_cookies.iter().count() < 2 {
         let window = windows.single();
         let width = window.width();
@@ -377,8 +605,6 @@ pub fn golden_cookie_spawn_system(
         let x = margin_x + pseudo_random() * (width - margin_x * 2.0);
         let y = margin_y + pseudo_random() * (height - margin_y * 2.0);
 
-        let multiplier = 7 + (pseudo_random() * 14.0) as u64;
-
         commands.spawn((
             ButtonBundle {
                 style: Style {
@@ -400,7 +626,6 @@ pub fn golden_cookie_spawn_system(
             },
             GoldenCookie {
                 lifetime: Timer::from_seconds(10.0, TimerMode::Once),
-                multiplier,
             },
         ))
             .with_children(|parent| {
@@ -414,10 +639,55 @@ pub fn golden_cookie_spawn_system(
                 ));
             });
 
+        sounds.send(PlaySound(Sfx::GoldenSpawn));
+        if settings.golden_alert {
+            spawn_achievement_popup(&mut commands, "⭐", &tr("popup.golden.spawned"), "", 0.0, &tr("popup.golden.title"));
+        }
+
         timer.0 = Timer::from_seconds(random_spawn_time(), TimerMode::Once);
     }
 }
 
+/// Keeps the buff column in step with `GameState::buffs`: rows are respawned when
+/// the list (or the language) changes, bars and timers move every frame.
+pub fn buff_bar_system(
+    mut commands: Commands,
+    game_state: Res<GameState>,
+    fonts: Res<UiFonts>,
+    bar_query: Query<Entity, With<BuffBar>>,
+    mut fill_query: Query<(&mut Style, &BuffFill)>,
+    mut time_query: Query<(&mut Text, &BuffTimeText)>,
+    mut shown: Local<Vec<String>>,
+) {
+    let names: Vec<String> = game_state.buffs.iter().map(|b| b.name(&game_state)).collect();
+    if *shown != names {
+        for bar in &bar_query {
+            commands.entity(bar)
+                .despawn_descendants()
+                .with_children(|bar| {
+                    for (index, (buff, name)) in game_state.buffs.iter().zip(&names).enumerate() {
+                        spawn_buff_row(bar, &fonts, index, buff.def.emoji, name, buff.is_debuff());
+                    }
+                });
+        }
+        *shown = names;
+    }
+
+    for (mut style, fill) in &mut fill_query {
+        if let Some(buff) = game_state.buffs.get(fill.0) {
+            style.width = Val::Percent(buff.fraction_left() * 100.0);
+        }
+    }
+    for (mut text, time) in &mut time_query {
+        if let Some(buff) = game_state.buffs.get(time.0) {
+            let value = format_duration(buff.remaining.ceil() as u64);
+            if text.sections[0].value != value {
+                text.sections[0].value = value;
+            }
+        }
+    }
+}
+
 pub fn golden_cookie_lifetime_system(
     mut commands: Commands,
     time: Res<Time>,
@@ -441,24 +711,35 @@ pub fn golden_cookie_lifetime_system(
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn golden_cookie_click_system(
     mut commands: Commands,
-    mut interaction_query: Query<(Entity, &Interaction, &GoldenCookie), Changed<Interaction>>,
+    mut interaction_query: Query<(Entity, &Interaction), (Changed<Interaction>, With<GoldenCookie>)>,
     mut game_state: ResMut<GameState>,
+    mut sounds: EventWriter<PlaySound>,
 ) {
-    for (entity, interaction, golden) in &mut interaction_query {
-        if *interaction == Interaction::Pressed {
-            let mut bonus = game_state.cookies_per_second as u128 * golden.multiplier as u128 * 60;
-            if bonus == 0{
-                bonus = 2000;
-            }
-            game_state.cookies += bonus;
-            game_state.total_cookies_earned += bonus;
-            game_state.lifetime_cookies += bonus;
-            game_state.golden_cookies_clicked += 1;
+    for (entity, interaction) in &mut interaction_query {
+        if *interaction == Interaction::Pressed && game_state.golden_allowed() {
+            let (effect, pick) = GoldenEffect::roll(pseudo_random());
+            let outcome = game_state.apply_golden_effect(effect, pick);
+            sounds.send(PlaySound(Sfx::GoldenClick));
 
             commands.entity(entity).despawn_recursive();
-            spawn_achievement_popup(&mut commands, "🍪", &format!("✨ Golden Cookie! +{} cookies", format_number(bonus)),"",0.0, "Golden Cookie!");
+            let (emoji, name) = match outcome {
+                GoldenOutcome::Cookies(cookies) => (
+                    "🍀".to_string(),
+                    tr_count("popup.golden.lucky", cookies.to_f64(), &[("count", format_number(cookies))]),
+                ),
+                GoldenOutcome::Buff(buff) => {
+                    let name = tr_with("popup.golden.buff", &[
+                        ("name", buff.name(&game_state)),
+                        ("multiplier", format_decimal(buff.def.multiplier)),
+                        ("duration", format_duration(buff.duration as u64)),
+                    ]);
+                    (buff.def.emoji.to_string(), name)
+                }
+            };
+            spawn_achievement_popup(&mut commands, &emoji, &name, "", 0.0, &tr("popup.golden.title"));
         }
     }
 }
@@ -495,6 +776,7 @@ pub fn combo_system(
 pub fn check_achievements_system(
     mut commands: Commands,
     mut game_state: ResMut<GameState>,
+    mut sounds: EventWriter<PlaySound>,
 ) {
     let mut to_unlock = Vec::new();
 
@@ -505,7 +787,7 @@ pub fn check_achievements_system(
                     game_state.total_cookies_earned >= *amount
                 }
                 AchievementRequirement::CookiesPerSecond(amount) => {
-                    game_state.cookies_per_second as u128 >= *amount
+                    game_state.cookies_per_second >= *amount
                 }
                 AchievementRequirement::Clicks(amount) => {
                     game_state.click_count >= *amount
@@ -513,12 +795,8 @@ pub fn check_achievements_system(
                 AchievementRequirement::GoldenCookies(amount) => {
                     game_state.golden_cookies_clicked >= *amount
                 }
-                AchievementRequirement::BuildingCount(index, count) => {
-                    if *index < game_state.upgrades.len() {
-                        game_state.upgrades[*index].count >= *count
-                    } else {
-                        false
-                    }
+                AchievementRequirement::BuildingCount(building, count) => {
+                    game_state.building(building).is_some_and(|u| u.count >= *count)
                 }
                 AchievementRequirement::PrestigeLevel(level) => {
                     game_state.prestige_level >= *level
@@ -526,6 +804,9 @@ pub fn check_achievements_system(
                 AchievementRequirement::Combos(amount) => {
                     game_state.combo >= *amount
                 }
+                AchievementRequirement::ChallengeCompleted(challenge) => {
+                    game_state.challenge_completed(challenge)
+                }
             };
 
             if unlocked {
@@ -534,74 +815,60 @@ pub fn check_achievements_system(
         }
     }
 
-    let mut popup_index = 0;
-
-    for (i, emoji, name, desc) in to_unlock {
+    if !to_unlock.is_empty() {
+        sounds.send(PlaySound(Sfx::Achievement));
+    }
+    for (popup_index, (i, emoji, name, desc)) in to_unlock.into_iter().enumerate() {
         game_state.achievements.unlocked[i] = true;
 
         spawn_achievement_popup(
             &mut commands,
-            &*emoji,
-            &*name,
-            &*desc,
+            &emoji,
+            &name,
+            &desc,
             popup_index as f32 * 90.0,
-            "Achievement Unlocked!"
+            &tr("popup.achievement.title"),
         );
-
-        popup_index += 1;
     }
 
 }
 
+/// Title shown under the logo, by total cookies earned, highest first.
+const MILESTONES: [(f64, &str, &str); 20] = [
+    (1e21, "🌌", "milestone.deesse"),
+    (1e20, "✨", "milestone.entite"),
+    (1e19, "👁️", "milestone.architecte"),
+    (1e18, "🔥", "milestone.imperatrice"),
+    (1e17, "🌠", "milestone.reine_cosmique"),
+    (1e16, "💫", "milestone.souveraine"),
+    (1e15, "🏆", "milestone.legende"),
+    (1e14, "💎", "milestone.maitresse_absolue"),
+    (1e13, "👑", "milestone.grande_reine"),
+    (1e12, "👸", "milestone.reine"),
+    (1e11, "🌟", "milestone.duchesse"),
+    (1e10, "🎖️", "milestone.marquise"),
+    (1e9, "⭐", "milestone.baronne"),
+    (1e8, "🍰", "milestone.dame"),
+    (1e7, "🥐", "milestone.experte"),
+    (1e6, "🍪", "milestone.boulangere"),
+    (1e5, "🧁", "milestone.patissiere"),
+    (1e4, "🥖", "milestone.apprentie"),
+    (1e3, "🎀", "milestone.novice"),
+    (0.0, "🌱", "milestone.debutante"),
+];
+
 pub fn milestone_system(
     game_state: Res<GameState>,
     mut milestone_query: Query<&mut Text, With<MilestoneText>>,
 ) {
     for mut text in &mut milestone_query {
-        let milestone = if game_state.total_cookies_earned >= 1_000_000_000_000_000_000_000 {
-            "🌌 Déesse des Cookies"
-        } else if game_state.total_cookies_earned >= 100_000_000_000_000_000_000 {
-            "✨ Entité Suprême du Four"
-        } else if game_state.total_cookies_earned >= 10_000_000_000_000_000_000 {
-            "👁️ Architecte du Multivers Sucré"
-        } else if game_state.total_cookies_earned >= 1_000_000_000_000_000_000 {
-            "🔥 Impératrice des Cookies"
-        } else if game_state.total_cookies_earned >= 100_000_000_000_000_000 {
-            "🌠 Reine Cosmique du Sucre"
-        } else if game_state.total_cookies_earned >= 10_000_000_000_000_000 {
-            "💫 Souveraine des Dimensions Sucrées"
-        } else if game_state.total_cookies_earned >= 1_000_000_000_000_000 {
-            "🏆 Légende des Cookies"
-        } else if game_state.total_cookies_earned >= 100_000_000_000_000 {
-            "💎 Maîtresse Absolue du Four"
-        } else if game_state.total_cookies_earned >= 10_000_000_000_000 {
-            "👑 Grande Reine des Cookies"
-        } else if game_state.total_cookies_earned >= 1_000_000_000_000 {
-            "👸 Reine des Cookies"
-        } else if game_state.total_cookies_earned >= 100_000_000_000 {
-            "🌟 Duchesse du Sucre"
-        } else if game_state.total_cookies_earned >= 10_000_000_000 {
-            "🎖️ Marquise des Cookies"
-        } else if game_state.total_cookies_earned >= 1_000_000_000 {
-            "⭐ Baronne des Cookies"
-        } else if game_state.total_cookies_earned >= 100_000_000 {
-            "🍰 Dame du Four"
-        } else if game_state.total_cookies_earned >= 10_000_000 {
-            "🥐 Experte Pâtissière"
-        } else if game_state.total_cookies_earned >= 1_000_000 {
-            "🍪 Maîtresse Boulangère"
-        } else if game_state.total_cookies_earned >= 100_000 {
-            "🧁 Pâtissière Confirmée"
-        } else if game_state.total_cookies_earned >= 10_000 {
-            "🥖 Apprentie Boulangère"
-        } else if game_state.total_cookies_earned >= 1_000 {
-            "🎀 Novice du Sucre"
-        } else {
-            "🌱 Débutante"
-        };
-
-
-        text.sections[0].value = milestone.to_string();
+        let (_, emoji, key) = MILESTONES.iter()
+            .find(|(threshold, _, _)| game_state.total_cookies_earned >= BigNum::from(*threshold))
+            .unwrap_or(&MILESTONES[MILESTONES.len() - 1]);
+        let milestone = format!("{} {}", emoji, tr(key));
+        if text.sections[0].value != milestone {
+            text.sections[0].value = milestone;
+        }
     }
 }
 
@@ -644,7 +911,7 @@ pub fn animate_popup_system(
         let alpha = 1.0 - progress;
         let _scale = 1.0 + progress * 0.4;
         text.sections[0].style.color.set_alpha(alpha);
-        text.sections[0].style.font_size = 40.0;
+        text.sections[0].style.font_size = popup.font_size;
     }
 }
 
@@ -691,71 +958,77 @@ pub fn particle_cleanup_system(
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn update_ui_system(
     game_state: Res<GameState>,
+    buy_amount: Res<BuyAmount>,
     mut cookie_query: Query<&mut Text, With<CookieCounter>>,
     mut cps_query: Query<&mut Text, (With<CpsCounter>, Without<CookieCounter>)>,
     mut stats_query: Query<&mut Text, (With<StatsText>, Without<CookieCounter>, Without<CpsCounter>)>,
     mut upgrade_query: Query<(&mut Text, &UpgradeText), (Without<CookieCounter>, Without<CpsCounter>, Without<StatsText>)>,
 ) {
     for mut text in &mut cookie_query {
-        text.sections[0].value = format!("{} cookies", format_number(game_state.cookies));
+        text.sections[0].value = tr_count("ui.cookies", game_state.cookies.to_f64(), &[("count", format_number(game_state.cookies))]);
     }
 
     for mut text in &mut cps_query {
-        text.sections[0].value = format!("par seconde: {}", format_number(game_state.cookies_per_second as u128));
+        text.sections[0].value = tr_with("ui.per_second", &[("cps", format_number(game_state.cookies_per_second))]);
     }
 
     for mut text in &mut stats_query {
-        text.sections[0].value = format!(
-            "📊 Statistiques:\n\
-            Total gagné: {}\n\
-            Puissance de clic: {}\n\
-            Clics: {}\n\
-            Golden cookies: {}\n\
-            Prestige: ⭐ Niveau {} [{}%]",
-            format_number(game_state.total_cookies_earned),
-            format_number(game_state.cookies_per_click),
-            format_number(game_state.click_count),
-            game_state.golden_cookies_clicked,
-            game_state.prestige_level, (game_state.prestige_level + 1) as f64 * PRESTIGE_BUFF * 100.0
-        );
+        text.sections[0].value = tr_with("ui.stats.body", &[
+            ("total", format_number(game_state.total_cookies_earned)),
+            ("click", format_number(game_state.cookies_per_click)),
+            ("clicks", format_number(game_state.click_count)),
+            ("golden", game_state.golden_cookies_clicked.to_string()),
+            ("level", game_state.prestige_level.to_string()),
+            ("bonus", ((game_state.prestige_level + 1) as f64 * PRESTIGE_BUFF * 100.0).to_string()),
+        ]);
     }
 
     for (mut text, upgrade_text) in &mut upgrade_query {
-        if upgrade_text.upgrade_index < game_state.upgrades.len() {
-            let upgrade = &game_state.upgrades[upgrade_text.upgrade_index];
+        let Some(index) = game_state.building_index(&upgrade_text.building) else {
+            continue;
+        };
+        if let Some((quantity, price)) = game_state.building_order(index, *buy_amount) {
+            let upgrade = &game_state.upgrades[index];
             text.sections[0].value = format!(
                 "{} {} [{}]\n{}\n💰 {} | ⚡ {}/s",
                 upgrade.emoji,
                 upgrade.name,
                 upgrade.count,
                 upgrade.description,
-                format_number(upgrade.cost),
-                format_number(upgrade.cps as u128)
+                format_price(price, quantity),
+                format_decimal(game_state.building_cps(index))
             );
         }
     }
 }
 
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 pub fn update_stats_system(
     game_state: Res<GameState>,
+    buy_amount: Res<BuyAmount>,
     combo: Res<ComboSystem>,
     mut powerup_query: Query<(&mut Text, &PowerUpText), Without<ComboText>>,
     mut combo_query: Query<&mut Text, (With<ComboText>, Without<PowerUpText>, Without<AchievementText>)>,
     mut achievement_query: Query<&mut Text, (With<AchievementText>, Without<ComboText>, Without<PowerUpText>)>,
     mut prestige_query: Query<&mut Text, (With<PrestigeText>, Without<ComboText>, Without<PowerUpText>, Without<AchievementText>)>,
+    mut prestige_fill_query: Query<&mut Style, With<PrestigeFill>>,
 ) {
     for (mut text, powerup_text) in &mut powerup_query {
-        if powerup_text.powerup_index < game_state.powerups.len() {
-            let powerup = &game_state.powerups[powerup_text.powerup_index];
+        let Some(index) = game_state.powerup_index(&powerup_text.powerup) else {
+            continue;
+        };
+        if let Some((quantity, price)) = game_state.powerup_order(index, *buy_amount) {
+            let powerup = &game_state.powerups[index];
             text.sections[0].value = format!(
                 "{} {} [{}]\n{}\n💰 {}",
                 powerup.emoji,
                 powerup.name,
                 powerup.count,
                 powerup.description,
-                format_number(powerup.cost)
+                format_price(price, quantity)
             );
         }
     }
@@ -784,42 +1057,572 @@ pub fn update_stats_system(
         text.sections[0].value = achievements_str;
     }
 
+    // The bar and the ETA always aim at the next level: the first one, or one more
+    // than prestiging now would grant.
+    let progress = game_state.prestige_progress();
+    let eta = game_state.prestige_eta().map_or_else(|| tr("ui.prestige.eta_unknown"), format_duration);
     for mut text in &mut prestige_query {
-        let prestige_cookies_needed = prestige_requirement((game_state.prestige_level + 200) as u64);
-        if game_state.cookies >= prestige_cookies_needed {
-            let bonus = 1.0 * PRESTIGE_BUFF * 100.0;
-            text.sections[0].value = format!(
-                "⭐ PRESTIGE DISPONIBLE!\nBonus: +{:.0}% production\nNiveau suivant: {}",
-                bonus,
-                game_state.prestige_level + 1
-            );
+        let gain = game_state.prestige_gain();
+        if gain > 0 {
+            let bonus = gain as f64 * PRESTIGE_BUFF * 100.0;
+            text.sections[0].value = tr_count("ui.prestige.ready", gain as f64, &[
+                ("levels", gain.to_string()),
+                ("bonus", format!("{:.0}", bonus)),
+                ("eta", eta.clone()),
+            ]);
         } else {
-            let progress = game_state.cookies as f64 / prestige_cookies_needed as f64 * 100.0;
-            text.sections[0].value = format!(
-                "Prestige: {:.4}%\nRequis: {}",
-                progress,
-                format_number(prestige_cookies_needed)
-            );
+            text.sections[0].value = tr_with("ui.prestige.progress", &[
+                ("progress", format!("{:.1}", progress * 100.0)),
+                ("required", format_number(game_state.prestige_cost())),
+                ("eta", eta.clone()),
+            ]);
         }
     }
+    for mut style in &mut prestige_fill_query {
+        style.width = Val::Percent(progress * 100.0);
+    }
 }
 
-pub fn prestige_requirement(level: u64) -> u128 {
-    let base = 1e11;
-    let exponent = 1.15 + (level as f64 * 0.005);
-    (base * (level as f64 + 1.0).powf(exponent)) as u128
-}
-
-
 pub fn auto_save_system(
     mut save_timer: ResMut<SaveTimer>,
-    game_state: Res<GameState>,
+    mut game_state: ResMut<GameState>,
+    location: Res<SaveLocation>,
+    settings: Res<Settings>,
     time: Res<Time>,
 ) {
+    let interval = Duration::from_secs(settings.autosave_seconds);
+    if save_timer.0.duration() != interval {
+        save_timer.0.set_duration(interval);
+    }
     save_timer.0.tick(time.delta());
 
     if save_timer.0.just_finished() {
-        save_game_state(&*game_state);
+        save_game_state(&mut game_state, &location);
         println!("💾 Sauvegarde automatique");
     }
 }
+
+pub fn offline_progress_popup_system(
+    mut commands: Commands,
+    offline: Option<Res<OfflineProgress>>,
+) {
+    let Some(offline) = offline else {
+        return;
+    };
+
+    spawn_achievement_popup(
+        &mut commands,
+        "🌙",
+        &tr_count("popup.offline.cookies", offline.cookies.to_f64(), &[("count", format_number(offline.cookies))]),
+        &tr_with("popup.offline.away", &[("duration", format_duration(offline.seconds))]),
+        0.0,
+        &tr("popup.offline.title"),
+    );
+    commands.remove_resource::<OfflineProgress>();
+}
+
+pub fn save_recovery_popup_system(
+    mut commands: Commands,
+    recovery: Option<Res<SaveRecovery>>,
+) {
+    let Some(recovery) = recovery else {
+        return;
+    };
+
+    spawn_achievement_popup(
+        &mut commands,
+        "💾",
+        &tr("popup.recovery.name"),
+        &recovery.file,
+        90.0,
+        &tr("popup.recovery.title"),
+    );
+    commands.remove_resource::<SaveRecovery>();
+}
+
+/// Swaps in another game (slot switch, import) and resets the per-session state.
+fn replace_game_state(
+    game_state: &mut GameState,
+    combo: &mut ComboSystem,
+    state: GameState,
+) {
+    combo.clicks = 0;
+    combo.combo = 0;
+    combo.active = false;
+    *game_state = state;
+}
+
+/// Hot reload of `assets/data/`: progress is carried over by id, like a
+/// save/load round trip, and the shop lists are rebuilt.
+pub fn catalog_reload_system(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<ContentPack>>,
+    handles: Option<Res<CatalogHandles>>,
+    packs: Res<Assets<ContentPack>>,
+    fonts: Res<UiFonts>,
+    lists: Query<(Entity, &ShopList)>,
+    mut game_state: ResMut<GameState>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+    let Some(handles) = handles else {
+        return;
+    };
+
+    let catalog = match loaded_catalog(&handles, &packs) {
+        Some(Ok(catalog)) => catalog,
+        Some(Err(e)) => {
+            eprintln!("❌ Catalogue invalide, ancien contenu conservé : {}", e);
+            return;
+        }
+        None => return,
+    };
+    if *crate::catalog::current() == catalog {
+        return;
+    }
+
+    crate::catalog::install(catalog);
+    rebuild_content(&mut commands, &fonts, &lists, &mut game_state);
+    println!("🔄 Catalogue rechargé");
+}
+
+/// Reads the catalogs again, in the current language, keeping progress like a
+/// save/load round trip, and respawns the shop lists to match.
+fn rebuild_content(
+    commands: &mut Commands,
+    fonts: &UiFonts,
+    lists: &Query<(Entity, &ShopList)>,
+    game_state: &mut GameState,
+) {
+    *game_state = SaveData::from_game_state(game_state).into_game_state();
+
+    for (entity, list) in lists {
+        let ids = shop_ids(game_state, list.0);
+        commands.entity(entity)
+            .despawn_descendants()
+            .with_children(|list_builder| spawn_shop_items(list_builder, fonts, list.0, &ids));
+    }
+}
+
+/// Escape or the gear button opens the settings menu; either closes it again.
+pub fn settings_menu_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<SettingsButton>)>,
+    menu_query: Query<Entity, With<SettingsMenu>>,
+    fonts: Res<UiFonts>,
+    settings: Res<Settings>,
+) {
+    let pressed = interaction_query.iter().any(|i| *i == Interaction::Pressed);
+    if !pressed && !keys.just_pressed(KeyCode::Escape) {
+        return;
+    }
+
+    if menu_query.is_empty() {
+        spawn_settings_menu(&mut commands, &fonts, &settings);
+    } else {
+        for entity in &menu_query {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn setting_button_system(
+    interaction_query: Query<(&Interaction, &SettingButton), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
+) {
+    for (interaction, button) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            settings.cycle(button.setting);
+        }
+    }
+}
+
+/// Holding the mouse on a volume slider sets the volume under the cursor.
+pub fn volume_slider_system(
+    slider_query: Query<(&Interaction, &RelativeCursorPosition, &VolumeSlider)>,
+    mut settings: ResMut<Settings>,
+) {
+    for (interaction, cursor, slider) in &slider_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(position) = cursor.normalized else {
+            continue;
+        };
+
+        let before = settings.volume(slider.setting);
+        let mut after = settings.clone();
+        after.set_volume(slider.setting, position.x);
+        if after.volume(slider.setting) != before {
+            *settings = after;
+        }
+    }
+}
+
+/// Writes changed settings to disk and refreshes the menu. Content names and
+/// descriptions are written when the catalogs are read, so a new language or
+/// notation rebuilds the state; fixed labels follow in `localized_text_system`.
+#[allow(clippy::too_many_arguments)]
+pub fn apply_settings_system(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    location: Res<SaveLocation>,
+    fonts: Res<UiFonts>,
+    lists: Query<(Entity, &ShopList)>,
+    mut value_query: Query<(&mut Text, &SettingValue)>,
+    mut fill_query: Query<(&mut Style, &SliderFill)>,
+    mut game_state: ResMut<GameState>,
+) {
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+
+    if settings.language != language() || settings.notation != notation() {
+        settings.apply();
+        rebuild_content(&mut commands, &fonts, &lists, &mut game_state);
+    }
+
+    for (mut text, value) in &mut value_query {
+        text.sections[0].value = settings.label(value.0);
+    }
+    for (mut style, fill) in &mut fill_query {
+        style.width = Val::Percent(settings.volume(fill.0) * 100.0);
+    }
+
+    if let Err(e) = settings.save(&location.dir) {
+        eprintln!("❌ Impossible d'enregistrer les réglages : {}", e);
+    }
+}
+
+pub fn localized_text_system(
+    game_state: Res<GameState>,
+    mut label_query: Query<(&mut Text, &LocalizedText)>,
+    mut title_query: Query<(&mut Text, &ShopSectionTitle), Without<LocalizedText>>,
+) {
+    for (mut text, label) in &mut label_query {
+        let value = tr_with(label.key, &label.args);
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+
+    for (mut text, title) in &mut title_query {
+        let value = section_title(&game_state, title.0);
+        if text.sections[1].value != value {
+            text.sections[1].value = value;
+        }
+    }
+}
+
+/// Price of a purchase, with the unit count when it is more than one.
+fn format_price(price: BigNum, quantity: u64) -> String {
+    if quantity > 1 {
+        format!("{} (x{})", format_number(price), quantity)
+    } else {
+        format_number(price)
+    }
+}
+
+pub fn buy_amount_button_system(
+    mut interaction_query: Query<(&Interaction, &mut BackgroundColor, &BuyAmountButton)>,
+    mut buy_amount: ResMut<BuyAmount>,
+) {
+    for (interaction, mut color, button) in &mut interaction_query {
+        if *interaction == Interaction::Pressed {
+            *buy_amount = button.amount;
+        }
+
+        let active = button.amount == *buy_amount;
+        *color = match (*interaction, active) {
+            (_, true) => Color::srgb(0.8, 0.6, 0.1).into(),
+            (Interaction::Hovered, false) => Color::srgb(0.35, 0.3, 0.45).into(),
+            _ => Color::srgb(0.25, 0.2, 0.35).into(),
+        };
+    }
+}
+
+pub fn save_slot_button_system(
+    mut commands: Commands,
+    mut interaction_query: Query<(&Interaction, &mut BackgroundColor, &SaveSlotButton)>,
+    mut game_state: ResMut<GameState>,
+    mut location: ResMut<SaveLocation>,
+    mut combo: ResMut<ComboSystem>,
+) {
+    for (interaction, mut color, slot_button) in &mut interaction_query {
+        let active = slot_button.slot == location.slot;
+
+        match *interaction {
+            Interaction::Pressed => {
+                if !active {
+                    save_game_state(&mut game_state, &location);
+
+                    location.slot = slot_button.slot;
+                    location.remember_slot();
+                    let (mut state, recovery) = load_or_create_game_state(&location);
+                    if let Some(offline) = apply_offline_progress(&mut state, now_secs()) {
+                        commands.insert_resource(offline);
+                    }
+                    if let Some(recovery) = recovery {
+                        commands.insert_resource(recovery);
+                    }
+
+                    replace_game_state(&mut game_state, &mut combo, state);
+                    println!("💾 Slot {} chargé", location.slot);
+                }
+                *color = Color::srgb(0.9, 0.7, 0.1).into();
+            }
+            Interaction::Hovered => {
+                *color = if active {
+                    Color::srgb(0.9, 0.7, 0.1).into()
+                } else {
+                    Color::srgb(0.35, 0.3, 0.45).into()
+                };
+            }
+            Interaction::None => {
+                *color = if active {
+                    Color::srgb(0.8, 0.6, 0.1).into()
+                } else {
+                    Color::srgb(0.25, 0.2, 0.35).into()
+                };
+            }
+        }
+    }
+}
+
+pub fn save_transfer_button_system(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &SaveTransferButton), Changed<Interaction>>,
+    dialog_query: Query<(), With<ConfirmDialog>>,
+    game_state: Res<GameState>,
+    location: Res<SaveLocation>,
+    fonts: Res<UiFonts>,
+) {
+    for (interaction, button) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            SaveTransferButton::Export => {
+                let text = match export_save(&SaveData::from_game_state(&game_state)) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        eprintln!("❌ Export impossible : {}", e);
+                        continue;
+                    }
+                };
+
+                let file = location.dir.join(format!("export_slot_{}.txt", location.slot));
+                if let Err(e) = fs::create_dir_all(&location.dir).and_then(|_| fs::write(&file, &text)) {
+                    eprintln!("❌ Export impossible : {}", e);
+                }
+
+                let copied = arboard::Clipboard::new()
+                    .and_then(|mut clipboard| clipboard.set_text(text.clone()))
+                    .is_ok();
+                let desc = if copied {
+                    tr("popup.export.clipboard")
+                } else {
+                    file.display().to_string()
+                };
+                println!("📤 Sauvegarde exportée : {}", desc);
+                spawn_achievement_popup(&mut commands, "📤", &tr("popup.export.name"), &desc, 0.0, &tr("popup.export.title"));
+            }
+            SaveTransferButton::Import => {
+                match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+                    Ok(text) => {
+                        open_import(&mut commands, &fonts, &dialog_query, &text);
+                    }
+                    Err(_) => spawn_achievement_popup(
+                        &mut commands,
+                        "📥",
+                        &tr("popup.import.no_clipboard"),
+                        &tr("popup.import.drop_hint"),
+                        0.0,
+                        &tr("popup.import.title"),
+                    ),
+                }
+            }
+        }
+    }
+}
+
+pub fn import_file_drop_system(
+    mut commands: Commands,
+    mut drop_events: EventReader<FileDragAndDrop>,
+    dialog_query: Query<(), With<ConfirmDialog>>,
+    fonts: Res<UiFonts>,
+) {
+    for event in drop_events.read() {
+        if let FileDragAndDrop::DroppedFile { path_buf, .. } = event {
+            match fs::read_to_string(path_buf) {
+                Ok(text) => {
+                    // The dialog only exists from next frame on: one import per batch of files.
+                    if open_import(&mut commands, &fonts, &dialog_query, &text) {
+                        break;
+                    }
+                }
+                Err(e) => eprintln!("❌ Lecture de {} impossible : {}", path_buf.display(), e),
+            }
+        }
+    }
+}
+
+/// Asks to confirm importing `text`. Refused while another dialog is open, so
+/// the save confirmed is always the one previewed. Returns whether it asked.
+fn open_import(
+    commands: &mut Commands,
+    fonts: &UiFonts,
+    dialog_query: &Query<(), With<ConfirmDialog>>,
+    text: &str,
+) -> bool {
+    if !dialog_query.is_empty() {
+        return false;
+    }
+
+    match import_save(text) {
+        Ok(save) => {
+            let body = format!(
+                "{}\n\n{}\n{}\n{}",
+                tr("dialog.import.question"),
+                tr_count("dialog.import.cookies", save.cookies.to_f64(), &[("count", format_number(save.cookies))]),
+                tr_with("dialog.import.level", &[("level", save.prestige_level.to_string())]),
+                tr_count("dialog.import.clicks", save.click_count as f64, &[("count", format_number(save.click_count))]),
+            );
+            spawn_confirm_dialog(commands, fonts, DialogAction::Import, &tr("dialog.import.title"), &body);
+            commands.insert_resource(PendingImport(save));
+            true
+        }
+        Err(e) => {
+            eprintln!("❌ Import refusé : {}", e);
+            spawn_achievement_popup(commands, "📥", &tr("popup.import.rejected"), &e, 0.0, &tr("popup.import.title"));
+            false
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn confirm_dialog_system(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &DialogButton), Changed<Interaction>>,
+    dialog_query: Query<Entity, With<ConfirmDialog>>,
+    pending: Option<Res<PendingImport>>,
+    pending_prestige: Option<Res<PendingPrestige>>,
+    mut game_state: ResMut<GameState>,
+    location: Res<SaveLocation>,
+    mut combo: ResMut<ComboSystem>,
+    fonts: Res<UiFonts>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    for (interaction, button) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        if button.confirms == Some(DialogAction::Import)
+            && let Some(pending) = &pending
+        {
+            let mut state = pending.0.clone().into_game_state();
+            state.last_saved = now_secs();
+            replace_game_state(&mut game_state, &mut combo, state);
+            save_game_state(&mut game_state, &location);
+            spawn_achievement_popup(&mut commands, "📥", &tr("popup.import.done"), "", 0.0, &tr("popup.import.title"));
+        }
+
+        if button.confirms == Some(DialogAction::Prestige)
+            && let Some(pending_prestige) = &pending_prestige
+            && let Some(summary) = game_state.ascend(now_secs())
+        {
+            if let Some(challenge) = &pending_prestige.challenge
+                && game_state.start_challenge(challenge)
+            {
+                println!("🏆 Défi commencé : {}", challenge);
+            }
+            combo.clicks = 0;
+            combo.combo = 0;
+            combo.active = false;
+            save_game_state(&mut game_state, &location);
+            sounds.send(PlaySound(Sfx::Achievement));
+            println!("⭐ PRESTIGE! Niveau {}", game_state.prestige_level);
+            spawn_run_summary(&mut commands, &fonts, &summary, &game_state.run_history);
+        }
+
+        commands.remove_resource::<PendingImport>();
+        commands.remove_resource::<PendingPrestige>();
+        for entity in &dialog_query {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Highlights the challenge picked in the prestige dialog and shows its rules.
+pub fn challenge_choice_system(
+    mut interaction_query: Query<(&Interaction, &ChallengeChoiceButton, &mut BackgroundColor)>,
+    mut description_query: Query<&mut Text, With<ChallengeDescriptionText>>,
+    pending: Option<ResMut<PendingPrestige>>,
+) {
+    let Some(mut pending) = pending else {
+        return;
+    };
+
+    for (interaction, choice, mut color) in &mut interaction_query {
+        if *interaction == Interaction::Pressed && pending.challenge != choice.challenge {
+            pending.challenge = choice.challenge.clone();
+        }
+        *color = if pending.challenge == choice.challenge {
+            Color::srgb(0.6, 0.3, 0.8).into()
+        } else if *interaction == Interaction::Hovered {
+            Color::srgb(0.35, 0.28, 0.48).into()
+        } else {
+            Color::srgb(0.25, 0.2, 0.35).into()
+        };
+    }
+
+    if pending.is_changed() {
+        let description = pending.challenge.as_deref()
+            .and_then(challenge_def)
+            .map_or_else(String::new, |def| def.description());
+        for mut text in &mut description_query {
+            text.sections[0].value = description.clone();
+        }
+    }
+}
+
+/// Ends the challenge when its goal is baked and keeps its progress on screen.
+pub fn challenge_system(
+    mut commands: Commands,
+    mut game_state: ResMut<GameState>,
+    mut text_query: Query<&mut Text, With<ChallengeText>>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    if let Some(def) = game_state.check_challenge() {
+        sounds.send(PlaySound(Sfx::Achievement));
+        println!("🏆 Défi réussi : {}", def.id);
+        spawn_achievement_popup(&mut commands, def.emoji, &def.name(), &def.reward_description(), 0.0, &tr("popup.challenge.title"));
+    }
+
+    let status = game_state.active_challenge().map_or_else(String::new, |def| {
+        tr_with("ui.challenge.active", &[
+            ("emoji", def.emoji.to_string()),
+            ("name", def.name()),
+            ("progress", format!("{:.1}", game_state.challenge_progress() * 100.0)),
+        ])
+    });
+    for mut text in &mut text_query {
+        text.sections[0].value = status.clone();
+    }
+}
+
+pub fn run_summary_system(
+    mut commands: Commands,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<RunSummaryButton>)>,
+    screen_query: Query<Entity, With<RunSummaryScreen>>,
+) {
+    if interaction_query.iter().any(|i| *i == Interaction::Pressed) {
+        for entity in &screen_query {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
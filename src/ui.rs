@@ -4,7 +4,19 @@ use crate::ui_icons::icon;
 use bevy::asset::AssetPath;
 use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
-use crate::resources::GameState;
+use bevy::ui::RelativeCursorPosition;
+use crate::resources::{BuyAmount, GameState};
+use crate::save::SAVE_SLOTS;
+use crate::settings::{Setting, Settings};
+use crate::locale::{tr, tr_with};
+use crate::challenges::CHALLENGES;
+use crate::heavenly::heavenly_tiers;
+use crate::runs::RunSummary;
+use crate::utils::{format_duration, format_number};
+
+/// Past runs listed under the summary of the one that just ended.
+const RUN_SUMMARY_HISTORY_ROWS: usize = 5;
+
 pub fn text(
     value: &str,
     font: Handle<Font>,
@@ -98,6 +110,22 @@ pub fn setup_ui(mut commands: Commands, assets: Res<AssetServer>, fonts: Res<UiF
                                 ..default()
                             })
                                 .with_children(|header| {
+                                    // Buffs actifs, dans le coin
+                                    header.spawn((
+                                        NodeBundle {
+                                            style: Style {
+                                                position_type: PositionType::Absolute,
+                                                top: Val::Px(16.0),
+                                                left: Val::Px(16.0),
+                                                flex_direction: FlexDirection::Column,
+                                                row_gap: Val::Px(6.0),
+                                                ..default()
+                                            },
+                                            ..default()
+                                        },
+                                        BuffBar,
+                                    ));
+
                                     // TITLE avec icône
                                     header
                                         .spawn(NodeBundle {
@@ -208,7 +236,6 @@ pub fn setup_ui(mut commands: Commands, assets: Res<AssetServer>, fonts: Res<UiF
                                             },
                                             Cookie,
                                             CookieScale {
-                                                base_scale: 1.0,
                                                 pulse: 0.0,
                                             },
                                         ))
@@ -254,13 +281,16 @@ pub fn setup_ui(mut commands: Commands, assets: Res<AssetServer>, fonts: Res<UiF
                                         })
                                         .with_children(|card| {
                                             card.spawn(emoji("📊", &fonts, 18.0, Color::srgb(0.7, 0.85, 1.0)));
-                                            card.spawn(TextBundle::from_section(
-                                                "Stats",
-                                                TextStyle {
-                                                    font: fonts.bold.clone(),
-                                                    font_size: 18.0,
-                                                    color: Color::srgb(0.7, 0.85, 1.0),
-                                                },
+                                            card.spawn((
+                                                TextBundle::from_section(
+                                                    tr("ui.stats.title"),
+                                                    TextStyle {
+                                                        font: fonts.bold.clone(),
+                                                        font_size: 18.0,
+                                                        color: Color::srgb(0.7, 0.85, 1.0),
+                                                    },
+                                                ),
+                                                LocalizedText::new("ui.stats.title"),
                                             ));
                                             card.spawn((
                                                 TextBundle::from_section(
@@ -273,6 +303,85 @@ pub fn setup_ui(mut commands: Commands, assets: Res<AssetServer>, fonts: Res<UiF
                                                 ),
                                                 StatsText,
                                             ));
+
+                                            // Save slots
+                                            card.spawn(NodeBundle {
+                                                style: Style {
+                                                    flex_direction: FlexDirection::Row,
+                                                    column_gap: Val::Px(6.0),
+                                                    ..default()
+                                                },
+                                                ..default()
+                                            })
+                                                .with_children(|row| {
+                                                    for slot in 1..=SAVE_SLOTS {
+                                                        row.spawn((
+                                                            ButtonBundle {
+                                                                style: Style {
+                                                                    padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                                                                    ..default()
+                                                                },
+                                                                background_color: Color::srgb(0.25, 0.2, 0.35).into(),
+                                                                border_radius: BorderRadius::all(Val::Px(8.0)),
+                                                                ..default()
+                                                            },
+                                                            SaveSlotButton { slot },
+                                                        ))
+                                                            .with_children(|btn| {
+                                                                let label = LocalizedText::new("ui.slot").with("slot", slot);
+                                                                btn.spawn((
+                                                                    text(&tr_with(label.key, &label.args), fonts.semibold.clone(), 13.0, Color::WHITE),
+                                                                    label,
+                                                                ));
+                                                            });
+                                                    }
+
+                                                    for (button, key) in [
+                                                        (SaveTransferButton::Export, "ui.export"),
+                                                        (SaveTransferButton::Import, "ui.import"),
+                                                    ] {
+                                                        row.spawn((
+                                                            ButtonBundle {
+                                                                style: Style {
+                                                                    padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                                                                    ..default()
+                                                                },
+                                                                background_color: Color::srgb(0.2, 0.3, 0.4).into(),
+                                                                border_radius: BorderRadius::all(Val::Px(8.0)),
+                                                                ..default()
+                                                            },
+                                                            button,
+                                                        ))
+                                                            .with_children(|btn| {
+                                                                btn.spawn((
+                                                                    text(&tr(key), fonts.semibold.clone(), 13.0, Color::WHITE),
+                                                                    LocalizedText::new(key),
+                                                                ));
+                                                            });
+                                                    }
+
+                                                    row.spawn((
+                                                        ButtonBundle {
+                                                            style: Style {
+                                                                padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                                                                column_gap: Val::Px(4.0),
+                                                                align_items: AlignItems::Center,
+                                                                ..default()
+                                                            },
+                                                            background_color: Color::srgb(0.3, 0.25, 0.2).into(),
+                                                            border_radius: BorderRadius::all(Val::Px(8.0)),
+                                                            ..default()
+                                                        },
+                                                        SettingsButton,
+                                                    ))
+                                                        .with_children(|btn| {
+                                                            btn.spawn(emoji("⚙", &fonts, 13.0, Color::WHITE));
+                                                            btn.spawn((
+                                                                text(&tr("ui.settings"), fonts.semibold.clone(), 13.0, Color::WHITE),
+                                                                LocalizedText::new("ui.settings"),
+                                                            ));
+                                                        });
+                                                });
                                         });
 
                                     // 2. ACHIEVEMENTS CARD
@@ -293,13 +402,16 @@ pub fn setup_ui(mut commands: Commands, assets: Res<AssetServer>, fonts: Res<UiF
                                         })
                                         .with_children(|card| {
                                             card.spawn(emoji("🏆", &fonts, 18.0, Color::srgb(1.0, 0.8, 0.3)));
-                                            card.spawn(TextBundle::from_section(
-                                                "Achievements",
-                                                TextStyle {
-                                                    font: fonts.bold.clone(),
-                                                    font_size: 18.0,
-                                                    color: Color::srgb(1.0, 0.8, 0.3),
-                                                },
+                                            card.spawn((
+                                                TextBundle::from_section(
+                                                    tr("ui.achievements.title"),
+                                                    TextStyle {
+                                                        font: fonts.bold.clone(),
+                                                        font_size: 18.0,
+                                                        color: Color::srgb(1.0, 0.8, 0.3),
+                                                    },
+                                                ),
+                                                LocalizedText::new("ui.achievements.title"),
                                             ));
                                             card.spawn((
                                                 TextBundle::from_section(
@@ -331,6 +443,18 @@ pub fn setup_ui(mut commands: Commands, assets: Res<AssetServer>, fonts: Res<UiF
                                             ..default()
                                         })
                                         .with_children(|card| {
+                                            card.spawn((
+                                                TextBundle::from_section(
+                                                    "",
+                                                    TextStyle {
+                                                        font: fonts.semibold.clone(),
+                                                        font_size: 14.0,
+                                                        color: Color::srgb(1.0, 0.6, 0.4),
+                                                    },
+                                                ),
+                                                ChallengeText,
+                                            ));
+
                                             card.spawn((
                                                 TextBundle::from_section(
                                                     "",
@@ -343,6 +467,32 @@ pub fn setup_ui(mut commands: Commands, assets: Res<AssetServer>, fonts: Res<UiF
                                                 PrestigeText,
                                             ));
 
+                                            card.spawn(NodeBundle {
+                                                style: Style {
+                                                    width: Val::Percent(90.0),
+                                                    height: Val::Px(10.0),
+                                                    ..default()
+                                                },
+                                                background_color: Color::srgb(0.2, 0.15, 0.3).into(),
+                                                border_radius: BorderRadius::all(Val::Px(5.0)),
+                                                ..default()
+                                            })
+                                                .with_children(|track| {
+                                                    track.spawn((
+                                                        NodeBundle {
+                                                            style: Style {
+                                                                width: Val::Percent(0.0),
+                                                                height: Val::Percent(100.0),
+                                                                ..default()
+                                                            },
+                                                            background_color: Color::srgb(0.8, 0.5, 1.0).into(),
+                                                            border_radius: BorderRadius::all(Val::Px(5.0)),
+                                                            ..default()
+                                                        },
+                                                        PrestigeFill,
+                                                    ));
+                                                });
+
                                             card.spawn((
                                                 ButtonBundle {
                                                     style: Style {
@@ -358,13 +508,38 @@ pub fn setup_ui(mut commands: Commands, assets: Res<AssetServer>, fonts: Res<UiF
                                             ))
                                                 .with_children(|btn| {
                                                     icon(btn, &assets, AssetPath::from("ui/icons/star.png"), 18.0);
-                                                    btn.spawn(TextBundle::from_section(
-                                                        "PRESTIGE",
-                                                        TextStyle {
-                                                            font: fonts.bold.clone(),
-                                                            font_size: 16.0,
-                                                            color: Color::WHITE,
-                                                        },
+                                                    btn.spawn((
+                                                        TextBundle::from_section(
+                                                            tr("ui.prestige.button"),
+                                                            TextStyle {
+                                                                font: fonts.bold.clone(),
+                                                                font_size: 16.0,
+                                                                color: Color::WHITE,
+                                                            },
+                                                        ),
+                                                        LocalizedText::new("ui.prestige.button"),
+                                                    ));
+                                                });
+
+                                            card.spawn((
+                                                ButtonBundle {
+                                                    style: Style {
+                                                        padding: UiRect::axes(Val::Px(14.0), Val::Px(6.0)),
+                                                        column_gap: Val::Px(6.0),
+                                                        align_items: AlignItems::Center,
+                                                        ..default()
+                                                    },
+                                                    background_color: Color::srgb(0.3, 0.35, 0.6).into(),
+                                                    border_radius: BorderRadius::all(Val::Px(10.0)),
+                                                    ..default()
+                                                },
+                                                HeavenlyButton,
+                                            ))
+                                                .with_children(|btn| {
+                                                    btn.spawn(emoji("☁", &fonts, 14.0, Color::WHITE));
+                                                    btn.spawn((
+                                                        text(&tr("ui.heavenly"), fonts.semibold.clone(), 14.0, Color::WHITE),
+                                                        LocalizedText::new("ui.heavenly"),
                                                     ));
                                                 });
                                         });
@@ -388,31 +563,76 @@ pub fn setup_ui(mut commands: Commands, assets: Res<AssetServer>, fonts: Res<UiF
                         ..default()
                     })
                         .with_children(|right| {
+                            right
+                                .spawn(NodeBundle {
+                                    style: Style {
+                                        flex_direction: FlexDirection::Row,
+                                        align_items: AlignItems::Center,
+                                        column_gap: Val::Px(6.0),
+                                        ..default()
+                                    },
+                                    ..default()
+                                })
+                                .with_children(|row| {
+                                    row.spawn((
+                                        text(&tr("ui.buy_amount"), fonts.semibold.clone(), 14.0, Color::srgb(0.7, 0.85, 1.0)),
+                                        LocalizedText::new("ui.buy_amount"),
+                                    ));
+                                    for amount in BuyAmount::ALL {
+                                        row.spawn((
+                                            ButtonBundle {
+                                                style: Style {
+                                                    padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                                                    ..default()
+                                                },
+                                                background_color: Color::srgb(0.25, 0.2, 0.35).into(),
+                                                border_radius: BorderRadius::all(Val::Px(8.0)),
+                                                ..default()
+                                            },
+                                            BuyAmountButton { amount },
+                                        ))
+                                            .with_children(|btn| {
+                                                btn.spawn(text(amount.label(), fonts.semibold.clone(), 13.0, Color::WHITE));
+                                            });
+                                    }
+                                });
+
                             section(
                                 right,
                                 &assets,
                                 &fonts,
                                 "⚡",
-                                &format!("POWER UPS [{}]", game_state.powerups.len()),
+                                &section_title(&game_state, ShopSection::PowerUps),
                                 AssetPath::from("ui/icons/power.png"),
-                                game_state.powerups.len(),
-                                true,
+                                &shop_ids(&game_state, ShopSection::PowerUps),
+                                ShopSection::PowerUps,
+                            );
+                            section(
+                                right,
+                                &assets,
+                                &fonts,
+                                "🔬",
+                                &section_title(&game_state, ShopSection::Research),
+                                AssetPath::from("ui/icons/star.png"),
+                                &shop_ids(&game_state, ShopSection::Research),
+                                ShopSection::Research,
                             );
                             section(
                                 right,
                                 &assets,
                                 &fonts,
                                 "🏭",
-                                &format!("BUILDINGS [{}]", game_state.upgrades.len()),
+                                &section_title(&game_state, ShopSection::Buildings),
                                 AssetPath::from("ui/icons/building.png"),
-                                game_state.upgrades.len(),
-                                false,
+                                &shop_ids(&game_state, ShopSection::Buildings),
+                                ShopSection::Buildings,
                             );
                         });
                 });
         }
 
 
+#[allow(clippy::collapsible_if)]
 pub fn mouse_scroll(
         mut mouse_wheel_events: EventReader<MouseWheel>,
         mut query_scroll: Query<(&mut ScrollingList, &mut Style, &Children, &Interaction), With<Node>>,
@@ -441,6 +661,7 @@ pub fn mouse_scroll(
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn section(
         parent: &mut ChildBuilder,
         _assets: &AssetServer,
@@ -448,13 +669,13 @@ pub fn mouse_scroll(
         emoji_title: &str,
         title: &str,
         _icon_path: AssetPath,
-        count: usize,
-        is_powerup: bool,
+        ids: &[String],
+        kind: ShopSection,
     ) {
         parent
             .spawn((NodeBundle {
                 style: Style {
-                    height: Val::Percent(48.0),
+                    height: Val::Percent(if kind == ShopSection::Research { 22.0 } else { 36.0 }),
                     flex_direction: FlexDirection::Column,
                     row_gap: Val::Px(12.0),
                     padding: UiRect::all(Val::Px(16.0)),
@@ -468,7 +689,7 @@ pub fn mouse_scroll(
             .with_children(|section| {
                 // HEADER
                 //section.spawn(emoji(emoji_title, fonts, 24.0, Color::srgb(0.9, 0.9, 1.0)));
-                section.spawn(TextBundle::from_sections([
+                section.spawn((TextBundle::from_sections([
                     TextSection::new(
                         emoji_title,
                         TextStyle {
@@ -485,7 +706,7 @@ pub fn mouse_scroll(
                             color: Color::srgb(0.7, 0.85, 1.0),
                         },
                     ),
-                ]));
+                ]), ShopSectionTitle(kind)));
 
                 // LIST SCROLLABLE
                 section
@@ -499,71 +720,125 @@ pub fn mouse_scroll(
                             ..default()
                         },
                         ScrollingList { position: 0.0 },
+                        ShopList(kind),
                     )).insert(Interaction::default())
                     .with_children(|list| {
-                        for i in 0..count {
-                            if is_powerup {
-                                list.spawn((
-                                    ButtonBundle {
-                                        style: Style {
-                                            padding: UiRect::all(Val::Px(14.0)),
-                                            border: UiRect::all(Val::Px(2.0)),
-                                            ..default()
-                                        },
-                                        background_color: Color::srgb(0.2, 0.18, 0.28).into(),
-                                        border_color: Color::srgba(0.5, 0.4, 0.7, 0.3).into(),
-                                        border_radius: BorderRadius::all(Val::Px(12.0)),
-                                        ..default()
-                                    },
-                                    PowerUpButton { powerup_index: i },
-                                )).insert(Interaction::default())
-                                    .with_children(|btn| {
-                                        btn.spawn((
-                                            TextBundle::from_section(
-                                                "Power Up",
-                                                TextStyle {
-                                                    font: fonts.semibold.clone(),
-                                                    font_size: 15.0,
-                                                    color: Color::srgb(0.9, 0.9, 1.0),
-                                                },
-                                            ),
-                                            PowerUpText { powerup_index: i },
-                                        ));
-                                    });
-                            } else {
-                                list.spawn((
-                                    ButtonBundle {
-                                        style: Style {
-                                            padding: UiRect::all(Val::Px(14.0)),
-                                            border: UiRect::all(Val::Px(2.0)),
-                                            ..default()
-                                        },
-                                        background_color: Color::srgb(0.2, 0.18, 0.28).into(),
-                                        border_color: Color::srgba(0.4, 0.6, 0.5, 0.3).into(),
-                                        border_radius: BorderRadius::all(Val::Px(12.0)),
-                                        ..default()
-                                    },
-                                    UpgradeButton { upgrade_index: i },
-                                ))
-                                    .with_children(|btn| {
-                                        btn.spawn((
-                                            TextBundle::from_section(
-                                                "Building",
-                                                TextStyle {
-                                                    font: fonts.semibold.clone(),
-                                                    font_size: 15.0,
-                                                    color: Color::srgb(0.9, 0.9, 1.0),
-                                                },
-                                            ),
-                                            UpgradeText { upgrade_index: i },
-                                        ));
-                                    });
-                            }
-                        }
+                        spawn_shop_items(list, fonts, kind, ids);
                     });
             });
     }
 
+    /// Heading of a shop section with its entry count, in the current language.
+    pub fn section_title(game_state: &GameState, kind: ShopSection) -> String {
+        let (key, count) = match kind {
+            ShopSection::PowerUps => ("ui.section.powerups", game_state.powerups.len()),
+            ShopSection::Research => ("ui.section.research", game_state.research.len()),
+            ShopSection::Buildings => ("ui.section.buildings", game_state.upgrades.len()),
+        };
+        tr_with(key, &[("count", count.to_string())])
+    }
+
+    /// Ids of the entries listed in a shop section, in display order.
+    pub fn shop_ids(game_state: &GameState, kind: ShopSection) -> Vec<String> {
+        match kind {
+            ShopSection::PowerUps => game_state.powerups.iter().map(|p| p.id.clone()).collect(),
+            ShopSection::Research => game_state.research.iter().map(|r| r.id.clone()).collect(),
+            ShopSection::Buildings => game_state.upgrades.iter().map(|u| u.id.clone()).collect(),
+        }
+    }
+
+    /// Buttons of one shop section, also used to rebuild the list when the catalog changes.
+    pub fn spawn_shop_items(list: &mut ChildBuilder, fonts: &UiFonts, kind: ShopSection, ids: &[String]) {
+        for id in ids {
+            if kind == ShopSection::Research {
+                list.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(10.0)),
+                            border: UiRect::all(Val::Px(2.0)),
+                            display: Display::None,
+                            ..default()
+                        },
+                        background_color: Color::srgb(0.2, 0.18, 0.28).into(),
+                        border_color: Color::srgba(0.7, 0.5, 0.4, 0.3).into(),
+                        border_radius: BorderRadius::all(Val::Px(12.0)),
+                        ..default()
+                    },
+                    ResearchButton { research: id.clone() },
+                ))
+                    .with_children(|btn| {
+                        btn.spawn((
+                            TextBundle::from_section(
+                                "Research",
+                                TextStyle {
+                                    font: fonts.semibold.clone(),
+                                    font_size: 14.0,
+                                    color: Color::srgb(0.9, 0.9, 1.0),
+                                },
+                            ),
+                            ResearchText { research: id.clone() },
+                        ));
+                    });
+            } else if kind == ShopSection::PowerUps {
+                list.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(14.0)),
+                            border: UiRect::all(Val::Px(2.0)),
+                            ..default()
+                        },
+                        background_color: Color::srgb(0.2, 0.18, 0.28).into(),
+                        border_color: Color::srgba(0.5, 0.4, 0.7, 0.3).into(),
+                        border_radius: BorderRadius::all(Val::Px(12.0)),
+                        ..default()
+                    },
+                    PowerUpButton { powerup: id.clone() },
+                )).insert(Interaction::default())
+                    .with_children(|btn| {
+                        btn.spawn((
+                            TextBundle::from_section(
+                                "Power Up",
+                                TextStyle {
+                                    font: fonts.semibold.clone(),
+                                    font_size: 15.0,
+                                    color: Color::srgb(0.9, 0.9, 1.0),
+                                },
+                            ),
+                            PowerUpText { powerup: id.clone() },
+                        ));
+                    });
+            } else {
+                list.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(14.0)),
+                            border: UiRect::all(Val::Px(2.0)),
+                            ..default()
+                        },
+                        background_color: Color::srgb(0.2, 0.18, 0.28).into(),
+                        border_color: Color::srgba(0.4, 0.6, 0.5, 0.3).into(),
+                        border_radius: BorderRadius::all(Val::Px(12.0)),
+                        ..default()
+                    },
+                    UpgradeButton { building: id.clone() },
+                ))
+                    .with_children(|btn| {
+                        btn.spawn((
+                            TextBundle::from_section(
+                                "Building",
+                                TextStyle {
+                                    font: fonts.semibold.clone(),
+                                    font_size: 15.0,
+                                    color: Color::srgb(0.9, 0.9, 1.0),
+                                },
+                            ),
+                            UpgradeText { building: id.clone() },
+                        ));
+                    });
+            }
+        }
+    }
+
     pub fn spawn_achievement_popup(
         commands: &mut Commands,
         _emoji: &str,
@@ -594,7 +869,6 @@ pub fn mouse_scroll(
             },
             AchievementPopup {
                 timer: Timer::from_seconds(4.0, TimerMode::Once),
-                index: 0,
             },
         ))
             .with_children(|parent| {
@@ -683,4 +957,590 @@ pub fn mouse_scroll(
                 commands.entity(entity).despawn_recursive();
             }
         }
-    }
\ No newline at end of file
+    }
+
+    /// Cancel and confirm buttons under `body`; confirming runs `action`.
+    pub fn spawn_confirm_dialog(
+        commands: &mut Commands,
+        fonts: &UiFonts,
+        action: DialogAction,
+        title: &str,
+        body: &str,
+    ) {
+        spawn_confirm_dialog_with(commands, fonts, action, title, body, |_| {});
+    }
+
+    /// Same dialog with `extra` content between the body and the buttons.
+    pub fn spawn_confirm_dialog_with(
+        commands: &mut Commands,
+        fonts: &UiFonts,
+        action: DialogAction,
+        title: &str,
+        body: &str,
+        extra: impl FnOnce(&mut ChildBuilder),
+    ) {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+                    z_index: ZIndex::Global(300),
+                    ..default()
+                },
+                Interaction::default(),
+                ConfirmDialog,
+            ))
+            .with_children(|overlay| {
+                overlay
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(420.0),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            padding: UiRect::all(Val::Px(24.0)),
+                            row_gap: Val::Px(16.0),
+                            border: UiRect::all(Val::Px(2.0)),
+                            ..default()
+                        },
+                        background_color: Color::srgba(0.15, 0.1, 0.2, 0.98).into(),
+                        border_color: Color::srgb(1.0, 0.7, 0.2).into(),
+                        border_radius: BorderRadius::all(Val::Px(16.0)),
+                        ..default()
+                    })
+                    .with_children(|card| {
+                        card.spawn(text(title, fonts.bold.clone(), 22.0, Color::srgb(1.0, 0.7, 0.2)));
+                        card.spawn(text(body, fonts.regular.clone(), 16.0, Color::WHITE));
+                        extra(card);
+
+                        card.spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                                column_gap: Val::Px(16.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                            .with_children(|row| {
+                                for (confirms, key, color) in [
+                                    (None, "ui.cancel", Color::srgb(0.35, 0.25, 0.25)),
+                                    (Some(action), "ui.confirm", Color::srgb(0.3, 0.6, 0.4)),
+                                ] {
+                                    row.spawn((
+                                        ButtonBundle {
+                                            style: Style {
+                                                padding: UiRect::axes(Val::Px(20.0), Val::Px(10.0)),
+                                                ..default()
+                                            },
+                                            background_color: color.into(),
+                                            border_radius: BorderRadius::all(Val::Px(12.0)),
+                                            ..default()
+                                        },
+                                        DialogButton { confirms },
+                                    ))
+                                        .with_children(|btn| {
+                                            btn.spawn((
+                                                text(&tr(key), fonts.bold.clone(), 16.0, Color::WHITE),
+                                                LocalizedText::new(key),
+                                            ));
+                                        });
+                                }
+                            });
+                    });
+            });
+    }
+
+    /// Challenge picker of the prestige dialog: a normal run, then every challenge,
+    /// ✅ on those completed once. The rules of the one picked show underneath.
+    pub fn spawn_challenge_choices(card: &mut ChildBuilder, fonts: &UiFonts, game_state: &GameState) {
+        card.spawn(text(&tr("dialog.prestige.challenge"), fonts.semibold.clone(), 15.0, Color::srgb(0.9, 0.7, 1.0)));
+
+        card.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(8.0),
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            ..default()
+        })
+            .with_children(|row| {
+                let choices = std::iter::once(None).chain(CHALLENGES.iter().map(Some));
+                for def in choices {
+                    row.spawn((
+                        ButtonBundle {
+                            style: Style {
+                                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                                column_gap: Val::Px(6.0),
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: Color::srgb(0.25, 0.2, 0.35).into(),
+                            border_radius: BorderRadius::all(Val::Px(10.0)),
+                            ..default()
+                        },
+                        ChallengeChoiceButton { challenge: def.map(|d| d.id.to_string()) },
+                    ))
+                        .with_children(|btn| {
+                            let Some(def) = def else {
+                                btn.spawn(text(&tr("challenge.none"), fonts.semibold.clone(), 14.0, Color::WHITE));
+                                return;
+                            };
+                            btn.spawn(emoji(def.emoji, fonts, 14.0, Color::WHITE));
+                            btn.spawn(text(&def.name(), fonts.semibold.clone(), 14.0, Color::WHITE));
+                            if game_state.challenge_completed(def.id) {
+                                btn.spawn(emoji("✅", fonts, 12.0, Color::WHITE));
+                            }
+                        });
+                }
+            });
+
+        card.spawn((
+            text("", fonts.regular.clone(), 14.0, Color::srgb(0.85, 0.85, 0.9)),
+            ChallengeDescriptionText,
+        ));
+    }
+
+    /// Screen shown once a prestige went through: the stats of the run that ended,
+    /// then the last few runs of the history, newest first.
+    pub fn spawn_run_summary(commands: &mut Commands, fonts: &UiFonts, summary: &RunSummary, history: &[RunSummary]) {
+        let duration = |run: &RunSummary| run.duration().map_or_else(|| tr("summary.unknown"), format_duration);
+        let stats = [
+            tr_with("summary.duration", &[("duration", duration(summary))]),
+            tr_with("summary.cookies", &[("count", format_number(summary.cookies))]),
+            tr_with("summary.clicks", &[("count", format_number(summary.clicks as f64))]),
+            tr_with("summary.golden", &[("count", format_number(summary.golden_cookies as f64))]),
+            tr_with("summary.combo", &[("count", summary.best_combo.to_string())]),
+            tr_with("summary.chips", &[("count", format_number(summary.chips))]),
+        ].join("\n");
+        let past = history.iter().enumerate().rev()
+            .take(RUN_SUMMARY_HISTORY_ROWS)
+            .map(|(index, run)| tr_with("summary.history.row", &[
+                ("run", (index + 1).to_string()),
+                ("level", run.level.to_string()),
+                ("duration", duration(run)),
+                ("cookies", format_number(run.cookies)),
+            ]))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.7).into(),
+                    z_index: ZIndex::Global(300),
+                    ..default()
+                },
+                Interaction::default(),
+                RunSummaryScreen,
+            ))
+            .with_children(|overlay| {
+                overlay
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(440.0),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            padding: UiRect::all(Val::Px(24.0)),
+                            row_gap: Val::Px(14.0),
+                            border: UiRect::all(Val::Px(2.0)),
+                            ..default()
+                        },
+                        background_color: Color::srgba(0.15, 0.1, 0.25, 0.98).into(),
+                        border_color: Color::srgb(0.9, 0.7, 1.0).into(),
+                        border_radius: BorderRadius::all(Val::Px(16.0)),
+                        ..default()
+                    })
+                    .with_children(|card| {
+                        card.spawn(text(&tr("summary.title"), fonts.bold.clone(), 26.0, Color::srgb(0.9, 0.7, 1.0)));
+                        card.spawn(text(
+                            &tr_with("summary.level", &[("level", summary.level.to_string())]),
+                            fonts.semibold.clone(),
+                            18.0,
+                            Color::srgb(1.0, 0.85, 0.4),
+                        ));
+                        card.spawn(text(&stats, fonts.regular.clone(), 16.0, Color::WHITE));
+                        card.spawn(text(&tr("summary.history"), fonts.semibold.clone(), 15.0, Color::srgb(0.7, 0.85, 1.0)));
+                        card.spawn(text(&past, fonts.regular.clone(), 13.0, Color::srgb(0.8, 0.8, 0.9)));
+
+                        card.spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    padding: UiRect::axes(Val::Px(20.0), Val::Px(10.0)),
+                                    ..default()
+                                },
+                                background_color: Color::srgb(0.6, 0.3, 0.8).into(),
+                                border_radius: BorderRadius::all(Val::Px(12.0)),
+                                ..default()
+                            },
+                            RunSummaryButton,
+                        ))
+                            .with_children(|btn| {
+                                btn.spawn((
+                                    text(&tr("ui.continue"), fonts.bold.clone(), 16.0, Color::WHITE),
+                                    LocalizedText::new("ui.continue"),
+                                ));
+                            });
+                    });
+            });
+    }
+
+    /// Overlay listing every setting with its current value; each press moves a
+    /// setting to its next value.
+    pub fn spawn_settings_menu(commands: &mut Commands, fonts: &UiFonts, settings: &Settings) {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+                    z_index: ZIndex::Global(250),
+                    ..default()
+                },
+                Interaction::default(),
+                SettingsMenu,
+            ))
+            .with_children(|overlay| {
+                overlay
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(460.0),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Stretch,
+                            padding: UiRect::all(Val::Px(24.0)),
+                            row_gap: Val::Px(12.0),
+                            border: UiRect::all(Val::Px(2.0)),
+                            ..default()
+                        },
+                        background_color: Color::srgba(0.15, 0.1, 0.2, 0.98).into(),
+                        border_color: Color::srgb(1.0, 0.7, 0.2).into(),
+                        border_radius: BorderRadius::all(Val::Px(16.0)),
+                        ..default()
+                    })
+                    .with_children(|card| {
+                        card.spawn((
+                            text(&tr("ui.settings"), fonts.bold.clone(), 22.0, Color::srgb(1.0, 0.7, 0.2)),
+                            LocalizedText::new("ui.settings"),
+                        ));
+
+                        for setting in Setting::ALL {
+                            card.spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Row,
+                                    justify_content: JustifyContent::SpaceBetween,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                                .with_children(|row| {
+                                    row.spawn((
+                                        text(&tr(setting.key()), fonts.regular.clone(), 16.0, Color::WHITE),
+                                        LocalizedText::new(setting.key()),
+                                    ));
+                                    if setting.is_volume() {
+                                        spawn_volume_slider(row, fonts, settings, setting);
+                                        return;
+                                    }
+                                    row.spawn((
+                                        ButtonBundle {
+                                            style: Style {
+                                                min_width: Val::Px(120.0),
+                                                padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                                                justify_content: JustifyContent::Center,
+                                                ..default()
+                                            },
+                                            background_color: Color::srgb(0.3, 0.25, 0.2).into(),
+                                            border_radius: BorderRadius::all(Val::Px(8.0)),
+                                            ..default()
+                                        },
+                                        SettingButton { setting },
+                                    ))
+                                        .with_children(|btn| {
+                                            btn.spawn((
+                                                text(&settings.label(setting), fonts.semibold.clone(), 14.0, Color::WHITE),
+                                                SettingValue(setting),
+                                            ));
+                                        });
+                                });
+                        }
+
+                        card.spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                                justify_content: JustifyContent::SpaceBetween,
+                                align_items: AlignItems::Center,
+                                margin: UiRect::top(Val::Px(8.0)),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                            .with_children(|row| {
+                                row.spawn((
+                                    text(&tr("settings.hint"), fonts.regular.clone(), 13.0, Color::srgb(0.7, 0.7, 0.8)),
+                                    LocalizedText::new("settings.hint"),
+                                ));
+                                row.spawn((
+                                    ButtonBundle {
+                                        style: Style {
+                                            padding: UiRect::axes(Val::Px(20.0), Val::Px(10.0)),
+                                            ..default()
+                                        },
+                                        background_color: Color::srgb(0.3, 0.6, 0.4).into(),
+                                        border_radius: BorderRadius::all(Val::Px(12.0)),
+                                        ..default()
+                                    },
+                                    SettingsButton,
+                                ))
+                                    .with_children(|btn| {
+                                        btn.spawn((
+                                            text(&tr("ui.close"), fonts.bold.clone(), 16.0, Color::WHITE),
+                                            LocalizedText::new("ui.close"),
+                                        ));
+                                    });
+                            });
+                    });
+            });
+    }
+
+    /// The heavenly upgrade tree, one row per depth: roots on top, each upgrade
+    /// below the ones it needs. Texts and colours are kept up to date by
+    /// `update_heavenly_ui_system`.
+    pub fn spawn_heavenly_menu(commands: &mut Commands, fonts: &UiFonts, game_state: &GameState) {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+                    z_index: ZIndex::Global(240),
+                    ..default()
+                },
+                Interaction::default(),
+                HeavenlyMenu,
+            ))
+            .with_children(|overlay| {
+                overlay
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            padding: UiRect::all(Val::Px(24.0)),
+                            row_gap: Val::Px(14.0),
+                            border: UiRect::all(Val::Px(2.0)),
+                            ..default()
+                        },
+                        background_color: Color::srgba(0.1, 0.12, 0.25, 0.98).into(),
+                        border_color: Color::srgb(0.7, 0.8, 1.0).into(),
+                        border_radius: BorderRadius::all(Val::Px(16.0)),
+                        ..default()
+                    })
+                    .with_children(|card| {
+                        card.spawn((
+                            text(&tr("ui.heavenly.title"), fonts.bold.clone(), 22.0, Color::srgb(0.8, 0.9, 1.0)),
+                            LocalizedText::new("ui.heavenly.title"),
+                        ));
+                        card.spawn((
+                            text("", fonts.semibold.clone(), 16.0, Color::srgb(1.0, 0.85, 0.4)),
+                            HeavenlyChipsText,
+                        ));
+
+                        for tier in heavenly_tiers(&game_state.heavenly) {
+                            card.spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Row,
+                                    justify_content: JustifyContent::Center,
+                                    column_gap: Val::Px(12.0),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                                .with_children(|row| {
+                                    for id in tier {
+                                        row.spawn((
+                                            ButtonBundle {
+                                                style: Style {
+                                                    width: Val::Px(190.0),
+                                                    padding: UiRect::all(Val::Px(10.0)),
+                                                    border: UiRect::all(Val::Px(2.0)),
+                                                    ..default()
+                                                },
+                                                background_color: Color::srgb(0.2, 0.2, 0.25).into(),
+                                                border_radius: BorderRadius::all(Val::Px(12.0)),
+                                                ..default()
+                                            },
+                                            HeavenlyNodeButton { upgrade: id.clone() },
+                                        ))
+                                            .with_children(|btn| {
+                                                btn.spawn((
+                                                    text("", fonts.semibold.clone(), 13.0, Color::srgb(0.9, 0.9, 1.0)),
+                                                    HeavenlyNodeText { upgrade: id },
+                                                ));
+                                            });
+                                    }
+                                });
+                        }
+
+                        card.spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    padding: UiRect::axes(Val::Px(20.0), Val::Px(10.0)),
+                                    margin: UiRect::top(Val::Px(8.0)),
+                                    ..default()
+                                },
+                                background_color: Color::srgb(0.3, 0.6, 0.4).into(),
+                                border_radius: BorderRadius::all(Val::Px(12.0)),
+                                ..default()
+                            },
+                            HeavenlyButton,
+                        ))
+                            .with_children(|btn| {
+                                btn.spawn((
+                                    text(&tr("ui.close"), fonts.bold.clone(), 16.0, Color::WHITE),
+                                    LocalizedText::new("ui.close"),
+                                ));
+                            });
+                    });
+            });
+    }
+
+    /// Bar set by clicking or dragging along it, with its value next to it.
+    fn spawn_volume_slider(row: &mut ChildBuilder, fonts: &UiFonts, settings: &Settings, setting: Setting) {
+        row.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(8.0),
+                ..default()
+            },
+            ..default()
+        })
+            .with_children(|slider_row| {
+                slider_row.spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(140.0),
+                            height: Val::Px(14.0),
+                            ..default()
+                        },
+                        background_color: Color::srgb(0.2, 0.17, 0.15).into(),
+                        border_radius: BorderRadius::all(Val::Px(7.0)),
+                        ..default()
+                    },
+                    RelativeCursorPosition::default(),
+                    VolumeSlider { setting },
+                ))
+                    .with_children(|bar| {
+                        bar.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Percent(settings.volume(setting) * 100.0),
+                                    height: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                background_color: Color::srgb(1.0, 0.7, 0.2).into(),
+                                border_radius: BorderRadius::all(Val::Px(7.0)),
+                                ..default()
+                            },
+                            SliderFill(setting),
+                        ));
+                    });
+                slider_row.spawn((
+                    text(&settings.label(setting), fonts.semibold.clone(), 14.0, Color::WHITE),
+                    SettingValue(setting),
+                ));
+            });
+    }
+
+    /// One line of the buff column: emoji, name, remaining-time bar and seconds left.
+    /// Debuffs are drawn in red.
+    pub fn spawn_buff_row(bar: &mut ChildBuilder, fonts: &UiFonts, index: usize, emoji_text: &str, name: &str, debuff: bool) {
+        let (name_color, fill_color) = if debuff {
+            (Color::srgb(1.0, 0.5, 0.45), Color::srgb(0.85, 0.2, 0.2))
+        } else {
+            (Color::srgb(1.0, 0.85, 0.4), Color::srgb(1.0, 0.7, 0.2))
+        };
+        bar.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(8.0),
+                padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                ..default()
+            },
+            background_color: Color::srgba(0.1, 0.08, 0.15, 0.85).into(),
+            border_radius: BorderRadius::all(Val::Px(8.0)),
+            ..default()
+        })
+            .with_children(|row| {
+                row.spawn(emoji(emoji_text, fonts, 18.0, Color::WHITE));
+                row.spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(3.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                    .with_children(|column| {
+                        column.spawn(text(name, fonts.semibold.clone(), 13.0, name_color));
+                        column.spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Px(140.0),
+                                height: Val::Px(6.0),
+                                ..default()
+                            },
+                            background_color: Color::srgb(0.2, 0.17, 0.15).into(),
+                            border_radius: BorderRadius::all(Val::Px(3.0)),
+                            ..default()
+                        })
+                            .with_children(|track| {
+                                track.spawn((
+                                    NodeBundle {
+                                        style: Style {
+                                            width: Val::Percent(100.0),
+                                            height: Val::Percent(100.0),
+                                            ..default()
+                                        },
+                                        background_color: fill_color.into(),
+                                        border_radius: BorderRadius::all(Val::Px(3.0)),
+                                        ..default()
+                                    },
+                                    BuffFill(index),
+                                ));
+                            });
+                    });
+                row.spawn((
+                    text("", fonts.regular.clone(), 13.0, Color::srgb(0.8, 0.8, 0.9)),
+                    BuffTimeText(index),
+                ));
+            });
+    }
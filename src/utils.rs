@@ -1,33 +1,231 @@
-pub fn format_number(num: u128) -> String {
-    const UNITS: [&str; 15] = [
-        "", "K", "M", "B", "T",
-        "Qa", "Qi", "Sx", "Sp", "Oc",
-        "No", "Dc", "Ud", "Dd", "Td"
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use crate::bignum::BigNum;
+use crate::locale::{self, Language};
+
+/// How large numbers are written. Below 1 000 every notation shows the plain
+/// integer part.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Notation {
+    /// `1.235M`, suffixes up to 1e93, then scientific.
+    #[default]
+    Short,
+    /// `1.235 milliard`: French long-scale names up to the décilliard, then scientific.
+    Long,
+    /// `1.23e45`
+    Scientific,
+    /// `123e42`: the exponent is always a multiple of three.
+    Engineering,
+    /// `1 234 567`: every digit up to 1e30, then scientific.
+    Full,
+}
+
+impl Notation {
+    pub const ALL: [Notation; 5] = [
+        Notation::Short,
+        Notation::Long,
+        Notation::Scientific,
+        Notation::Engineering,
+        Notation::Full,
     ];
 
-    if num < 1_000 {
-        return num.to_string();
+    pub fn next(self) -> Notation {
+        let index = Notation::ALL.iter().position(|n| *n == self).unwrap_or(0);
+        Notation::ALL[(index + 1) % Notation::ALL.len()]
+    }
+
+    /// String table key of the notation's name.
+    pub fn key(self) -> &'static str {
+        match self {
+            Notation::Short => "notation.short",
+            Notation::Long => "notation.long",
+            Notation::Scientific => "notation.scientific",
+            Notation::Engineering => "notation.engineering",
+            Notation::Full => "notation.full",
+        }
     }
+}
 
-    let mut unit = 0;
-    let mut value = num as f64;
+/// Notation used by `format_number`, set from `Settings`.
+static NOTATION: RwLock<Notation> = RwLock::new(Notation::Short);
+
+pub fn notation() -> Notation {
+    *NOTATION.read().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn set_notation(notation: Notation) {
+    *NOTATION.write().unwrap_or_else(|e| e.into_inner()) = notation;
+}
+
+/// One suffix per power of 1 000.
+const SHORT_UNITS: [&str; 32] = [
+    "", "K", "M", "B", "T",
+    "Qa", "Qi", "Sx", "Sp", "Oc",
+    "No", "Dc", "Ud", "Dd", "Td",
+    "Qad", "Qid", "Sxd", "Spd", "Ocd",
+    "Nod", "Vg", "Uvg", "Dvg", "Tvg",
+    "Qavg", "Qivg", "Sxvg", "Spvg", "Ocvg",
+    "Novg", "Tg",
+];
+
+/// Long-scale names from 1e6, one per power of 1 000.
+const LONG_UNITS: [&str; 20] = [
+    "million", "milliard", "billion", "billiard", "trillion",
+    "trilliard", "quadrillion", "quadrilliard", "quintillion", "quintilliard",
+    "sextillion", "sextilliard", "septillion", "septilliard", "octillion",
+    "octilliard", "nonillion", "nonilliard", "décillion", "décilliard",
+];
+
+/// `Full` switches to scientific from here, past any sensible width.
+const FULL_MAX_EXPONENT: i64 = 30;
+
+pub fn format_number(num: impl Into<BigNum>) -> String {
+    format_with(num.into(), notation(), locale::language())
+}
 
-    while value >= 1_000.0 && unit < UNITS.len() - 1 {
-        value /= 1_000.0;
-        unit += 1;
+/// Like `format_number`, but keeps up to two decimals of small values such as 0.1 cookies/sec.
+pub fn format_decimal(num: impl Into<BigNum>) -> String {
+    let num = num.into();
+    if num < BigNum::from(1_000.0) {
+        ((num.to_f64() * 100.0).round() / 100.0).to_string()
+    } else {
+        format_number(num)
+    }
+}
+
+pub fn format_with(num: BigNum, notation: Notation, language: Language) -> String {
+    if num < BigNum::from(1_000.0) {
+        return format!("{}", num.to_f64().floor());
+    }
+
+    match notation {
+        Notation::Short => {
+            let (digits, exponent) = round_digits(num, 4);
+            match SHORT_UNITS.get((exponent / 3) as usize) {
+                Some(unit) => format!("{}{}", group_mantissa(&digits, exponent, 3), unit),
+                None => scientific(num),
+            }
+        }
+        Notation::Long => {
+            if num < BigNum::from(1e6) {
+                return full_digits(num, language);
+            }
+            let (digits, exponent) = round_digits(num, 4);
+            let Some(name) = LONG_UNITS.get((exponent / 3 - 2) as usize) else {
+                return scientific(num);
+            };
+            let mantissa = group_mantissa(&digits, exponent, 3);
+            // French plurals start at 2, English long-scale names stay invariable.
+            let plural = language == Language::Fr && mantissa.parse::<f64>().is_ok_and(|m| m >= 2.0);
+            format!("{} {}{}", mantissa, name, if plural { "s" } else { "" })
+        }
+        Notation::Scientific => scientific(num),
+        Notation::Engineering => {
+            let (digits, exponent) = round_digits(num, 3);
+            format!("{}e{}", group_mantissa(&digits, exponent, 3), exponent - exponent.rem_euclid(3))
+        }
+        Notation::Full => {
+            if num.exponent() > FULL_MAX_EXPONENT {
+                scientific(num)
+            } else {
+                full_digits(num, language)
+            }
+        }
+    }
+}
+
+fn scientific(num: BigNum) -> String {
+    let (digits, exponent) = round_digits(num, 3);
+    format!("{}e{}", group_mantissa(&digits, exponent, 1), exponent)
+}
+
+/// Decimal digits of `num` rounded half-up to `significant` digits, with the exponent
+/// of the first one. Works on the decimal form rather than on floats so 999.95K
+/// becomes 1.000M, as on paper.
+fn round_digits(num: BigNum, significant: usize) -> (Vec<u8>, i64) {
+    let mut digits: Vec<u8> = format!("{}", num.mantissa()).bytes()
+        .filter(u8::is_ascii_digit)
+        .map(|b| b - b'0')
+        .collect();
+    let mut exponent = num.exponent();
+
+    let round_up = digits.get(significant).is_some_and(|&d| d >= 5);
+    digits.resize(significant, 0);
+    if round_up {
+        let mut i = significant;
+        loop {
+            if i == 0 {
+                // 9.999… carried all the way: one more power of ten.
+                digits.insert(0, 1);
+                digits.truncate(significant);
+                exponent += 1;
+                break;
+            }
+            i -= 1;
+            if digits[i] == 9 {
+                digits[i] = 0;
+            } else {
+                digits[i] += 1;
+                break;
+            }
+        }
     }
+    (digits, exponent)
+}
 
-    let decimals = if value >= 100.0 {
-        1
-    } else if value >= 10.0 {
-        2
+/// Writes the digits with the point placed for a group of `step` powers of ten.
+fn group_mantissa(digits: &[u8], exponent: i64, step: i64) -> String {
+    let integer = (exponent.rem_euclid(step) + 1) as usize;
+    let text: String = digits.iter().map(|d| char::from(b'0' + d)).collect();
+    if integer >= text.len() {
+        format!("{:0<width$}", text, width = integer)
     } else {
-        3
+        format!("{}.{}", &text[..integer], &text[integer..])
+    }
+}
+
+/// Every digit of the integer part, in groups of three.
+fn full_digits(num: BigNum, language: Language) -> String {
+    let exponent = num.exponent();
+    let (digits, exponent) = round_digits(num, (exponent + 1) as usize);
+    let text: String = digits.iter().map(|d| char::from(b'0' + d)).collect();
+    let text = format!("{:0<width$}", text, width = (exponent + 1) as usize);
+
+    let separator = match language {
+        Language::Fr => ' ',
+        Language::En => ',',
     };
+    let mut grouped = String::new();
+    for (i, c) in text.chars().enumerate() {
+        if i > 0 && (text.len() - i) % 3 == 0 {
+            grouped.push(separator);
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+pub fn format_duration(secs: u64) -> String {
+    let hours = secs / 3_600;
+    let minutes = (secs % 3_600) / 60;
+    let seconds = secs % 60;
 
-    format!("{:.*}{}", decimals, value, UNITS[unit])
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
 }
 
+pub fn now_secs() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
 
 pub fn random_spawn_time() -> f32 {
     30.0 + pseudo_random() * 390.0
@@ -40,4 +238,103 @@ pub fn pseudo_random() -> f32 {
         .unwrap()
         .subsec_nanos();
     ((nanos as f32 * 0.000000001) * 997.0) % 1.0
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::{QuickCheck, TestResult};
+
+    fn fmt(num: f64, notation: Notation) -> String {
+        format_with(BigNum::from(num), notation, Language::Fr)
+    }
+
+    #[test]
+    fn short_rounds_across_unit_boundaries() {
+        assert_eq!(fmt(999.0, Notation::Short), "999");
+        assert_eq!(fmt(1_234.0, Notation::Short), "1.234K");
+        assert_eq!(fmt(999_949.0, Notation::Short), "999.9K");
+        assert_eq!(fmt(999_950.0, Notation::Short), "1.000M");
+        assert_eq!(fmt(12_345_678.0, Notation::Short), "12.35M");
+        assert_eq!(fmt(1e93, Notation::Short), "1.000Tg");
+        assert_eq!(fmt(1e96, Notation::Short), "1.00e96");
+    }
+
+    #[test]
+    fn long_scale_names_agree_in_number() {
+        assert_eq!(fmt(123_456.0, Notation::Long), "123 456");
+        assert_eq!(fmt(1.5e9, Notation::Long), "1.500 milliard");
+        assert_eq!(fmt(2e9, Notation::Long), "2.000 milliards");
+        assert_eq!(format_with(BigNum::from(2e12), Notation::Long, Language::En), "2.000 billion");
+        assert_eq!(fmt(1e63, Notation::Long), "1.000 décilliard");
+        assert_eq!(fmt(1e66, Notation::Long), "1.00e66");
+    }
+
+    #[test]
+    fn scientific_and_engineering() {
+        assert_eq!(fmt(1.234e45, Notation::Scientific), "1.23e45");
+        assert_eq!(fmt(9.996e9, Notation::Scientific), "1.00e10");
+        assert_eq!(fmt(1.234e45, Notation::Engineering), "1.23e45");
+        assert_eq!(fmt(12_345.0, Notation::Engineering), "12.3e3");
+        assert_eq!(fmt(999_600.0, Notation::Engineering), "1.00e6");
+    }
+
+    #[test]
+    fn full_groups_digits_by_language() {
+        assert_eq!(fmt(1_234_567.0, Notation::Full), "1 234 567");
+        assert_eq!(format_with(BigNum::from(1_234_567.0), Notation::Full, Language::En), "1,234,567");
+        assert_eq!(fmt(1_000.0, Notation::Full), "1 000");
+        assert_eq!(fmt(1e31, Notation::Full), "1.00e31");
+    }
+
+    #[test]
+    fn decimals_are_kept_below_a_thousand() {
+        assert_eq!(format_decimal(0.1), "0.1");
+        assert_eq!(format_decimal(0.1 * 1.03), "0.1");
+        assert_eq!(format_decimal(1.48), "1.48");
+    }
+
+    /// Reads a formatted number back, or `None` if it is not in `notation`'s shape.
+    fn parse(text: &str, notation: Notation) -> Option<(f64, f64)> {
+        match notation {
+            Notation::Short if !text.contains('e') => {
+                let split = text.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(text.len());
+                let (mantissa, unit) = text.split_at(split);
+                let index = SHORT_UNITS.iter().position(|u| *u == unit)?;
+                let mantissa: f64 = mantissa.parse().ok()?;
+                Some((mantissa, mantissa * 1e3f64.powi(index as i32)))
+            }
+            _ => {
+                let (mantissa, _) = text.split_once('e')?;
+                Some((mantissa.parse().ok()?, text.parse().ok()?))
+            }
+        }
+    }
+
+    #[test]
+    fn formatted_numbers_stay_close_to_the_value() {
+        fn property(digits: u32, exponent: u16) -> TestResult {
+            let value = (1.0 + digits as f64 / u32::MAX as f64) * 10f64.powi(3 + exponent as i32 % 297);
+            for (notation, tolerance, step) in [
+                (Notation::Short, 5e-4, 1e3),
+                (Notation::Scientific, 5e-3, 10.0),
+                (Notation::Engineering, 5e-3, 1e3),
+            ] {
+                let text = fmt(value, notation);
+                let Some((mantissa, parsed)) = parse(&text, notation) else {
+                    return TestResult::error(format!("{} : {:?} illisible", value, text));
+                };
+                // Short falls back to scientific past its last unit.
+                let (tolerance, step) = if notation == Notation::Short && text.contains('e') {
+                    (5e-3, 10.0)
+                } else {
+                    (tolerance, step)
+                };
+                if !(1.0..step).contains(&mantissa) || ((parsed - value) / value).abs() > tolerance {
+                    return TestResult::error(format!("{} : {:?}", value, text));
+                }
+            }
+            TestResult::passed()
+        }
+        QuickCheck::new().tests(2_000).quickcheck(property as fn(u32, u16) -> TestResult);
+    }
+}